@@ -1,15 +1,44 @@
-
-
 extern crate serde_codegen;
 
 use std::env;
+use std::fs::File;
+use std::io::{Read, Write};
 use std::path::Path;
 
+/// Writes out the given file with all its serde attributes and
+/// `Serialize`/`Deserialize` derives taken out, for building
+/// without the `serde` feature.
+fn strip_serde(src: &Path, dst: &Path) {
+    let mut text = String::new();
+    File::open(src).unwrap().read_to_string(&mut text).unwrap();
+    let mut out = String::new();
+    for line in text.lines() {
+        if line.trim().starts_with("#[serde(") {
+            continue;
+        }
+        out.push_str(&line.replace("Serialize, Deserialize, ", ""));
+        out.push('\n');
+    }
+    File::create(dst).unwrap().write_all(out.as_bytes()).unwrap();
+}
+
 fn main() {
     let out_dir = env::var_os("OUT_DIR").unwrap();
+    let serde = env::var_os("CARGO_FEATURE_SERDE").is_some();
+
+    if serde {
+        let src = Path::new("src/serde_types.in.rs");
+        let dst = Path::new(&out_dir).join("serde_types.rs");
+
+        serde_codegen::expand(&src, &dst).unwrap();
+    }
 
-    let src = Path::new("src/serde_types.in.rs");
-    let dst = Path::new(&out_dir).join("serde_types.rs");
+    let src = Path::new("src/input_types.in.rs");
+    let dst = Path::new(&out_dir).join("input_types.rs");
 
-    serde_codegen::expand(&src, &dst).unwrap();
+    if serde {
+        serde_codegen::expand(&src, &dst).unwrap();
+    } else {
+        strip_serde(&src, &dst);
+    }
 }
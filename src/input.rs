@@ -11,11 +11,13 @@
 //! * Do some tweening of input axes and stuff just for
 //! fun maybe.
 //!
-//! Right now ggez doesn't handle joysticks or controllers
-//! anyway, so.
+//! Keyboard keys, mouse buttons, motion and wheel, and game
+//! controller buttons can all be bound.
 
+use std::cell::Cell;
+use std::fmt;
 use std::hash::Hash;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use ggez::event::*;
 
 
@@ -40,277 +42,5973 @@ use ggez::event::*;
 // Easy way?  Hash map of event -> axis/button bindings.
 
 #[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
-enum InputEvent {
-    KeyEvent(Keycode), // MouseButtonEvent,
+enum InputEvent<Key> {
+    // A key, plus the modifier keys that must be held with it
+    // (empty for a plain key binding).
+    KeyEvent(Key, Mod),
+    MouseButtonEvent(MouseButton),
+    MouseScrollEvent(ScrollDirection),
+    MouseMotionEvent(MouseAxis),
+    // Controller inputs, along with which controller they're from
+    ControllerButtonEvent(usize, Button),
+    ControllerAxisEvent(usize, Axis),
+    // The chord with the given index in InputManager::chords
+    ChordEvent(usize),
+    // Input from inject_button() or inject_axis()
+    InjectedEvent,
+    // One of the four ways a controller hat can be pushed, from the
+    // hat with the given id; the diagonals push two at once.
+    ControllerHatEvent(usize, HatPosition),
 }
 
-#[derive(Debug, Copy, Clone)]
+/// Which way the mouse wheel was scrolled.
+#[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Which way a controller's hat (usually its d-pad) is pushed.
+#[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
+pub enum HatPosition {
+    Centered,
+    Up,
+    RightUp,
+    Right,
+    RightDown,
+    Down,
+    LeftDown,
+    Left,
+    LeftUp,
+}
+
+impl HatPosition {
+    /// Whether being in this position counts as pushing the
+    /// given one of the four straight directions.
+    fn pushes(self, direction: HatPosition) -> bool {
+        use self::HatPosition::*;
+        match direction {
+            Up => self == Up || self == LeftUp || self == RightUp,
+            Down => self == Down || self == LeftDown || self == RightDown,
+            Left => self == Left || self == LeftUp || self == LeftDown,
+            Right => self == Right || self == RightUp || self == RightDown,
+            _ => false,
+        }
+    }
+}
+
+/// One of the two analog sticks on a game controller.
+#[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
+pub enum StickId {
+    Left,
+    Right,
+}
+
+/// How fast an axis moves, in units per second, as given to
+/// `InputManager::set_axis_tween()`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AxisTween {
+    /// How fast the axis moves toward where it's pushed
+    pub acceleration: f64,
+    /// How fast it falls back when let go of
+    pub gravity: f64,
+}
+
+impl AxisTween {
+    /// Works out the rates from how many frames at the given
+    /// framerate the axis should take to go all the way from 0 to 1
+    /// when pushed, and back again when let go of.  0 frames means
+    /// it goes there instantly, as with snapping.  Returns `None` if
+    /// the framerate isn't positive or either count of frames is
+    /// negative.
+    pub fn from_frames(accel_frames: f64, gravity_frames: f64, fps: f64) -> Option<AxisTween> {
+        if !(fps > 0.0 && accel_frames >= 0.0 && gravity_frames >= 0.0) {
+            return None;
+        }
+        let rate = |frames: f64| if frames == 0.0 { f64::INFINITY } else { fps / frames };
+        Some(AxisTween {
+            acceleration: rate(accel_frames),
+            gravity: rate(gravity_frames),
+        })
+    }
+}
+
+/// Shortens the vector (x, y) to length `max` if it's any longer,
+/// keeping its angle.  Shorter vectors, including zero, are returned
+/// as they are.  This is what `InputManager::get_axes_vector_normalized()`
+/// uses, with a `max` of 1.
+pub fn clamp_vector_magnitude(x: f64, y: f64, max: f64) -> (f64, f64) {
+    let length = f64::sqrt(x * x + y * y);
+    if length > max {
+        (x * max / length, y * max / length)
+    } else {
+        (x, y)
+    }
+}
+
+/// The error from `InputManager::try_bind_key_to_button()`
+/// when the key is already bound to something else.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindingConflict<Axes, Buttons>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone
+{
+    /// What the key is already bound to.
+    pub existing: BoundEffect<Axes, Buttons>,
+}
+
+impl<Axes, Buttons> fmt::Display for BindingConflict<Axes, Buttons>
+    where Axes: Eq + Hash + Clone + fmt::Debug,
+          Buttons: Eq + Hash + Clone + fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "key is already bound to {:?}", self.existing)
+    }
+}
+
+/// The error from `InputManager::decode_and_apply_frame()` when
+/// the frame is the wrong size for the frame layout.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FrameSizeError {
+    /// How many bytes the layout needs.
+    pub expected: usize,
+    /// How many bytes the frame had.
+    pub found: usize,
+}
+
+impl fmt::Display for FrameSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "frame is {} bytes, expected {}", self.found, self.expected)
+    }
+}
+
+/// How an axis combines key and analog inputs pushing on at once.
+/// See `InputManager::set_axis_blend()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AxisBlend {
+    /// Whichever input moved last sets the position.
+    LastWins,
+    /// Whichever input is further from 0 sets the position.
+    MaxMagnitude,
+    /// The inputs get added together.
+    Sum,
+}
+
+/// Something that happened to an axis or button, from
+/// `InputManager::drain_events()`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum InputAction<Axes, Buttons> {
+    ButtonPressed(Buttons),
+    ButtonReleased(Buttons),
+    /// The axis moved over an `update()`; `value` is what
+    /// `get_axis()` reports for it now.
+    AxisChanged { axis: Axes, value: f64 },
+}
+
+/// A logical axis or button, from `InputManager::unused_actions()`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Action<Axes, Buttons> {
+    Axis(Axes),
+    Button(Buttons),
+}
+
+/// A direction of mouse movement.
+#[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
+pub enum MouseAxis {
+    X,
+    Y,
+}
+
+/// One key press or release captured by `InputManager::start_recording()`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct InputFrame<Key = Keycode> {
+    /// Seconds since the recording started, as counted by the
+    /// `dt`'s given to `update()`.
+    pub time: f64,
+    /// Whether the key went down or up.
+    pub down: bool,
+    /// Whether this was a key repeat rather than a fresh press.
+    pub repeat: bool,
+    event: InputEvent<Key>,
+}
+
+/// A stand-in keyboard key, for testing input handling without
+/// needing ggez's keycodes.  Any character can be a key.
+#[cfg(any(test, feature = "mock-input"))]
+#[derive(Debug, Hash, Eq, PartialEq, Copy, Clone)]
+pub struct MockKey(pub char);
+
+/// An `InputManager` that takes `MockKey`s.
+#[cfg(any(test, feature = "mock-input"))]
+pub type MockInputManager<Axes, Buttons> = InputManager<Axes, Buttons, MockKey>;
+
+/// How an axis eases in and out as it moves between 0 and
+/// its ends.  See `InputManager::set_axis_curve()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AxisCurve {
+    /// Moves at a constant speed.
+    Linear,
+    /// Starts slow and speeds up.
+    Quadratic,
+    /// Like `Quadratic`, but more so.
+    Cubic,
+    /// Starts and ends slow.
+    Smoothstep,
+}
+
+impl AxisCurve {
+    /// Maps how far along the tween is, in [0, 1], to a position.
+    fn apply(&self, t: f64) -> f64 {
+        match *self {
+            AxisCurve::Linear => t,
+            AxisCurve::Quadratic => t * t,
+            AxisCurve::Cubic => t * t * t,
+            AxisCurve::Smoothstep => t * t * (3.0 - 2.0 * t),
+        }
+    }
+
+    /// The reverse of `apply()`, for when the position gets
+    /// set directly and the tween has to pick up from there.
+    fn inverse(&self, position: f64) -> f64 {
+        match *self {
+            AxisCurve::Linear => position,
+            AxisCurve::Quadratic => f64::sqrt(position),
+            AxisCurve::Cubic => f64::cbrt(position),
+            AxisCurve::Smoothstep => 0.5 - f64::sin(f64::asin(1.0 - 2.0 * position) / 3.0),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 enum InputEffect<Axes, Buttons>
     where Axes: Eq + Hash + Clone,
           Buttons: Eq + Hash + Clone
 {
-    Axis(Axes, bool),
+    // The axis, and the value from -1 to 1 it's pushed toward.
+    Axis(Axes, f64),
     Button(Buttons),
 }
 
+// BoundEffect and Bindings live here, since they need serde's codegen.
+// Without the `serde` feature, build.rs leaves out the derives instead.
+include!(concat!(env!("OUT_DIR"), "/input_types.rs"));
+
+impl<Axes, Buttons> From<InputEffect<Axes, Buttons>> for BoundEffect<Axes, Buttons>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone
+{
+    fn from(effect: InputEffect<Axes, Buttons>) -> Self {
+        match effect {
+            InputEffect::Axis(axis, value) => BoundEffect::Axis(axis, value),
+            InputEffect::Button(button) => BoundEffect::Button(button),
+        }
+    }
+}
+
+impl<Axes, Buttons> From<BoundEffect<Axes, Buttons>> for InputEffect<Axes, Buttons>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone
+{
+    fn from(effect: BoundEffect<Axes, Buttons>) -> Self {
+        match effect {
+            BoundEffect::Axis(axis, value) => InputEffect::Axis(axis, value),
+            BoundEffect::Button(button) => InputEffect::Button(button),
+        }
+    }
+}
+
 #[derive(Debug)]
-struct AxisStatus {
-    // Where the axis currently is, in [-1, 1]
+struct AxisStatus<Key> {
+    // Where the axis currently is, in [min, max]
     position: f64,
+    // The range the axis moves in, [-1, 1] unless set otherwise,
+    // and whether update() and mouse motion keep it inside it.
+    min: f64,
+    max: f64,
+    clamp: bool,
+    // Where the axis starts out and comes back to, 0 unless set
+    // otherwise.
+    initial: f64,
+    // How far from 0 being pushed takes the axis, if that's less
+    // than the ends of its range.
+    max_magnitude: f64,
+    // How close to its rest position the axis has to be to count
+    // as settled.
+    rest_tolerance: f64,
     // Where the axis is moving towards.  Possible
     // values are -1, 0, +1
     // (or a continuous range for analog devices I guess)
     direction: f64,
+    // -1 or 1 for which way the axis was last pushed, or 0 if it
+    // never has been
+    last_direction: f64,
+    // Inputs currently held that push the axis positive/negative,
+    // and how hard.  The direction is recomputed from these whenever
+    // one changes, so releasing one key doesn't cancel another that's
+    // still held.
+    positive_inputs: HashMap<InputEvent<Key>, f64>,
+    negative_inputs: HashMap<InputEvent<Key>, f64>,
     // Speed in units per second that the axis
-    // moves towards the target value.
+    // moves towards the target value, when pushed
+    // positive and negative respectively.
     acceleration: f64,
+    negative_acceleration: f64,
     // Speed in units per second that the axis will
     // fall back toward 0 if the input stops.
     gravity: f64,
+    // get_axis() reports 0 when the position is closer
+    // to 0 than this.
+    deadzone: f64,
+    // If true, get_axis() reports the position negated.
+    inverted: bool,
+    // get_axis() reports the position multiplied by this...
+    sensitivity: f64,
+    // ...and then clamped to [-1, 1] if this is true.
+    clamp_sensitivity: bool,
+    // If true, the axis jumps straight to its direction
+    // instead of accelerating/gravitating.
+    snap: bool,
+    // If true, there's no gravity; the axis stays where
+    // it is when nothing's pushing it.
+    hold: bool,
+    // If set, the stiffness and damping of a spring that pulls the
+    // axis back to rest instead of gravity, and how fast it's
+    // moving the axis.
+    spring: Option<(f64, f64)>,
+    spring_velocity: f64,
+    // The acceleration and gravity move this linearly, and the
+    // position is the curve applied to it, going from the rest
+    // position out to whichever end it's on the side of.
+    ramp: f64,
+    curve: AxisCurve,
+    // True if the position was last set by an analog input,
+    // in which case update() leaves it alone until a key
+    // takes over again.
+    analog: bool,
+    // With a blend other than LastWins, the position is worked out
+    // from where the keys have moved the axis to and the last
+    // analog value.
+    digital: f64,
+    analog_value: f64,
+    blend: AxisBlend,
+    // The position as of the last update(), and how fast it
+    // changed over that update in units per second.
+    last_position: f64,
+    velocity: f64,
+    // The positions as of the last few updates, oldest first,
+    // and how many of them to keep.  0 means none.
+    history: Vec<f64>,
+    history_samples: usize,
+    // How many updates the axis has ended at one end of its range.
+    saturation_count: u32,
+    // Whether the game has asked about the axis since the last
+    // reset_usage_tracking()
+    queried: Cell<bool>,
+}
+
+impl<Key: Eq + Hash> AxisStatus<Key> {
+    /// Recalculates the direction from the set of held inputs.
+    fn update_direction(&mut self) {
+        let positive = self.positive_inputs.values().fold(0.0, |acc, &m| f64::max(acc, m));
+        let negative = self.negative_inputs.values().fold(0.0, |acc, &m| f64::max(acc, m));
+        self.direction = positive - negative;
+        if self.direction != 0.0 {
+            self.last_direction = self.direction.signum();
+        }
+    }
+
+    /// Where gravity pulls the axis back to: its initial value,
+    /// or as close to it as the range allows.
+    fn rest(&self) -> f64 {
+        f64::max(self.min, f64::min(self.max, self.initial))
+    }
+
+    /// Moves the axis along its curve.
+    fn set_ramp(&mut self, ramp: f64) {
+        let rest = self.rest();
+        let span = if ramp >= rest { self.max - rest } else { self.min - rest };
+        self.ramp = ramp;
+        self.digital = if span == 0.0 {
+            rest
+        } else {
+            rest + span * self.curve.apply((ramp - rest) / span)
+        };
+        self.combine_sources();
+    }
+
+    /// Puts the axis at the given position, working out where
+    /// along its curve that is.
+    fn set_position(&mut self, position: f64) {
+        self.digital = position;
+        self.ramp = self.ramp_for(position);
+        self.combine_sources();
+    }
+
+    /// Where along its curve the axis is at the given position.
+    fn ramp_for(&self, position: f64) -> f64 {
+        let rest = self.rest();
+        let span = if position >= rest { self.max - rest } else { self.min - rest };
+        if span == 0.0 {
+            rest
+        } else {
+            rest + span * self.curve.inverse((position - rest) / span)
+        }
+    }
+
+    /// Works out the position from the key and analog inputs.
+    fn combine_sources(&mut self) {
+        self.position = match self.blend {
+            AxisBlend::LastWins => {
+                if self.analog { self.analog_value } else { self.digital }
+            }
+            AxisBlend::MaxMagnitude => {
+                if f64::abs(self.analog_value) > f64::abs(self.digital) {
+                    self.analog_value
+                } else {
+                    self.digital
+                }
+            }
+            AxisBlend::Sum => {
+                f64::max(self.min, f64::min(self.max, self.digital + self.analog_value))
+            }
+        };
+    }
+
+    /// Where snapping puts the axis for its current direction.
+    fn snap_target(&self) -> f64 {
+        let rest = self.rest();
+        if self.direction > 0.0 {
+            f64::min(rest + (self.max - rest) * self.direction, self.max_magnitude)
+        } else if self.direction < 0.0 {
+            f64::max(rest + (rest - self.min) * self.direction, -self.max_magnitude)
+        } else {
+            self.rest()
+        }
+    }
+
+    /// Keeps the given position within the range, if the axis clamps.
+    fn clamped(&self, position: f64) -> f64 {
+        if self.clamp {
+            f64::max(self.min, f64::min(self.max, position))
+        } else {
+            position
+        }
+    }
+
+    /// Moves the axis toward where it's being pushed, or back to rest.
+    fn tween(&mut self, dt: f64) {
+        if self.analog && self.blend == AxisBlend::LastWins {
+            // Analog inputs set the position directly.
+            return;
+        }
+        if self.direction != 0.0 {
+            self.spring_velocity = 0.0;
+        }
+        if self.snap {
+            let target = self.snap_target();
+            self.set_position(target);
+        } else if self.direction != 0.0 {
+            // Accelerate the axis towards the
+            // input'ed direction.  If it's only being pushed
+            // part way, or it's capped, it goes as far as that
+            // and no further, falling back to it at the gravity
+            // if it's already past.
+            let limited = self.max_magnitude.is_finite() || f64::abs(self.direction) < 1.0;
+            let target = if limited {
+                self.ramp_for(self.snap_target())
+            } else {
+                self.direction * f64::INFINITY
+            };
+            let speed = if (target - self.ramp) * self.direction < 0.0 {
+                self.gravity
+            } else if self.direction > 0.0 {
+                self.acceleration
+            } else {
+                self.negative_acceleration
+            };
+            let ramp = if speed == f64::INFINITY {
+                // Infinitely fast axes go straight there, stopping at
+                // the end of the range even if they aren't clamped.
+                if target.is_finite() {
+                    target
+                } else {
+                    self.ramp_for(self.snap_target())
+                }
+            } else if self.ramp < target {
+                f64::min(self.ramp + speed * dt, target)
+            } else {
+                f64::max(self.ramp - speed * dt, target)
+            };
+            let ramp = self.clamped(ramp);
+            self.set_ramp(ramp);
+        } else if !self.hold && self.spring.is_some() {
+            // Spring back towards the rest position, maybe
+            // overshooting it a bit.  Once it's close enough
+            // and slow enough it stops, so it doesn't wobble
+            // forever by tiny amounts.
+            let (stiffness, damping) = self.spring.unwrap();
+            let rest = self.rest();
+            let force = -stiffness * (self.ramp - rest) - damping * self.spring_velocity;
+            self.spring_velocity += force * dt;
+            let ramp = self.clamped(self.ramp + self.spring_velocity * dt);
+            if f64::abs(ramp - rest) < 1e-4 && f64::abs(self.spring_velocity) < 1e-3 {
+                self.spring_velocity = 0.0;
+                self.set_ramp(rest);
+            } else {
+                self.set_ramp(ramp);
+            }
+        } else if !self.hold {
+            // Gravitate back towards the rest position.
+            let rest = self.rest();
+            let dx = self.gravity * dt;
+            let ramp = if self.gravity == f64::INFINITY {
+                rest
+            } else if self.ramp > rest {
+                f64::max(self.ramp - dx, rest)
+            } else {
+                f64::min(self.ramp + dx, rest)
+            };
+            self.set_ramp(ramp);
+        }
+    }
+
+    /// Whether the axis is at one end of its range, other than
+    /// the one it rests at.
+    fn saturated(&self) -> bool {
+        (self.position >= self.max || self.position <= self.min) && self.position != self.rest()
+    }
+
+    /// Multiplies the position by the given factor.
+    fn scale_position(&mut self, factor: f64) {
+        self.analog_value *= factor;
+        let digital = self.digital * factor;
+        self.set_position(digital);
+    }
+
+    /// Puts the axis back at rest, and forgets any inputs
+    /// pushing on it.
+    fn reset(&mut self) {
+        let rest = self.rest();
+        self.direction = 0.0;
+        self.positive_inputs.clear();
+        self.negative_inputs.clear();
+        self.analog = false;
+        self.analog_value = 0.0;
+        self.spring_velocity = 0.0;
+        self.set_position(rest);
+    }
+
+    /// The value get_axis() reports for this axis.
+    fn output(&self) -> f64 {
+        if f64::abs(self.position) < self.deadzone {
+            return 0.0;
+        }
+        let mut value = self.position * self.sensitivity;
+        if self.clamp_sensitivity {
+            value = f64::max(-1.0, f64::min(1.0, value));
+        }
+        if self.inverted { -value } else { value }
+    }
 }
 
-impl Default for AxisStatus {
+impl<Key: Eq + Hash> Default for AxisStatus<Key> {
     fn default() -> Self {
         AxisStatus {
             position: 0.0,
+            min: -1.0,
+            max: 1.0,
+            clamp: true,
+            initial: 0.0,
+            max_magnitude: f64::INFINITY,
+            rest_tolerance: 0.001,
             direction: 0.0,
+            last_direction: 0.0,
+            positive_inputs: HashMap::new(),
+            negative_inputs: HashMap::new(),
             acceleration: 4.0,
+            negative_acceleration: 4.0,
             gravity: 3.0,
+            deadzone: 0.0,
+            inverted: false,
+            sensitivity: 1.0,
+            clamp_sensitivity: false,
+            snap: false,
+            hold: false,
+            spring: None,
+            spring_velocity: 0.0,
+            ramp: 0.0,
+            curve: AxisCurve::Linear,
+            analog: false,
+            digital: 0.0,
+            analog_value: 0.0,
+            blend: AxisBlend::LastWins,
+            last_position: 0.0,
+            velocity: 0.0,
+            history: Vec::new(),
+            history_samples: 0,
+            saturation_count: 0,
+            queried: Cell::new(false),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ButtonStatus<Key> {
+    // Whether the button is currently held down
+    pressed: bool,
+    // Inputs currently holding the button down.  It's pressed
+    // as long as any of them are.
+    held_by: HashSet<InputEvent<Key>>,
+    // Whether the button went down or up since the last update().
+    // These get cleared in update(), so they're only true for one frame.
+    pressed_edge: bool,
+    released_edge: bool,
+    // How long a press can wait to be consumed, in seconds
+    buffer: f64,
+    // Whether there's a press waiting to be consumed, and
+    // how much longer it can wait.
+    buffered: bool,
+    buffer_timer: f64,
+    // How long the button has been held down, in seconds
+    held_time: f64,
+    // How long ago it was last pressed and released, in seconds,
+    // if it ever has been.
+    since_press: Option<f64>,
+    since_release: Option<f64>,
+    // How long it has to be held to count as a long press.
+    // 0 means long presses are off.
+    long_press: f64,
+    // If true, each press flips whether the button is down,
+    // and letting go doesn't do anything.
+    toggle: bool,
+    // If not 0, the button releases itself after being held this
+    // long, and stays released until everything holding it lets go.
+    oneshot: f64,
+    oneshot_expired: bool,
+    // A second press within this many seconds of the first
+    // counts as a double tap.  0 means double taps are off.
+    double_tap_window: f64,
+    // When the button was last pressed, if that press could
+    // be the first half of a double tap.
+    last_press_time: Option<f64>,
+    // Set on the press that completes a double tap, and
+    // cleared in update() like the other edges.
+    double_tapped: bool,
+    // Whether the game has asked about the button since the last
+    // reset_usage_tracking()
+    queried: Cell<bool>,
+}
+
+impl<Key: Eq + Hash> ButtonStatus<Key> {
+    /// Clears the button's state, but not its settings.
+    fn reset(&mut self) {
+        *self = ButtonStatus {
+            buffer: self.buffer,
+            double_tap_window: self.double_tap_window,
+            long_press: self.long_press,
+            toggle: self.toggle,
+            oneshot: self.oneshot,
+            queried: self.queried.clone(),
+            ..ButtonStatus::default()
+        };
+    }
+}
+
+impl<Key: Eq + Hash> Default for ButtonStatus<Key> {
+    fn default() -> Self {
+        ButtonStatus {
+            pressed: false,
+            held_by: HashSet::new(),
+            pressed_edge: false,
+            released_edge: false,
+            buffer: 0.0,
+            buffered: false,
+            buffer_timer: 0.0,
+            held_time: 0.0,
+            since_press: None,
+            since_release: None,
+            long_press: 0.0,
+            toggle: false,
+            oneshot: 0.0,
+            oneshot_expired: false,
+            double_tap_window: 0.0,
+            last_press_time: None,
+            double_tapped: false,
+            queried: Cell::new(false),
         }
     }
 }
 
+// Something to call when a button is pressed or released.
+struct ButtonCallback(Box<dyn FnMut()>);
+
+impl fmt::Debug for ButtonCallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ButtonCallback")
+    }
+}
+
+// A set of keys that have to all be held at once to press a button.
+#[derive(Debug)]
+struct Chord<Key, Buttons> {
+    keys: Vec<Key>,
+    button: Buttons,
+    active: bool,
+}
+
+// A series of keys that have to be pressed in order, each within
+// `window` seconds of the last, like a fighting game special move.
+#[derive(Debug)]
+struct Sequence<Key, Buttons> {
+    id: Buttons,
+    steps: Vec<Key>,
+    window: f64,
+    triggered: bool,
+}
+
+// A mouse button whose drags move a pair of axes.
+#[derive(Debug)]
+struct MouseDrag<Axes> {
+    button: MouseButton,
+    x_axis: Axes,
+    y_axis: Axes,
+    pixels_per_unit: f64,
+    // Where the mouse was when the button went down, if it's held.
+    origin: Option<(i32, i32)>,
+}
+
+// Two axes read together as a stick by get_stick(), and the
+// radial deadzone to use for them.
+#[derive(Debug)]
+struct AxisPair<Axes> {
+    x_axis: Axes,
+    y_axis: Axes,
+    deadzone: f64,
+}
+
+type BindingMap<Axes, Buttons, Key> = HashMap<InputEvent<Key>, Vec<InputEffect<Axes, Buttons>>>;
+
+/// Keeps track of the state of all the axes and buttons, and which
+/// inputs are bound to them.  `Key` is the type of keyboard key;
+/// it's ggez's `Keycode` unless you want something else, like a
+/// simple enum of your own for testing.
 #[derive(Debug)]
-pub struct InputManager<Axes, Buttons>
+pub struct InputManager<Axes, Buttons, Key = Keycode>
     where Axes: Hash + Eq + Clone,
-          Buttons: Hash + Eq + Clone
+          Buttons: Hash + Eq + Clone,
+          Key: Hash + Eq + Clone
 {
     // Once EnumSet is stable it should be used for these
     // instead of BTreeMap. ♥?
-    // Binding of keys to input values.
-    bindings: HashMap<InputEvent, InputEffect<Axes, Buttons>>,
+    // Binding of keys to input values.  One input can do
+    // several things at once.
+    // These are the base bindings, used when no context is pushed.
+    bindings: BindingMap<Axes, Buttons, Key>,
+    // Named sets of bindings that can be pushed on top of the base
+    // ones.  Inputs the top one of the stack doesn't bind fall through
+    // to the ones below, until one that's opaque.
+    contexts: HashMap<String, BindingMap<Axes, Buttons, Key>>,
+    // The pushed contexts' names, and whether each is opaque
+    context_stack: Vec<(String, bool)>,
     // Input state for axes
-    axes: HashMap<Axes, AxisStatus>,
+    axes: HashMap<Axes, AxisStatus<Key>>,
     // Input states for buttons
-    buttons: HashMap<Buttons, bool>,
+    buttons: HashMap<Buttons, ButtonStatus<Key>>,
+    // Latest known cursor position
+    mouse_position: (i32, i32),
+    // Cursor position as of the last update()
+    last_mouse_position: (i32, i32),
+    // How far the wheel has scrolled since the last update(), and
+    // which scroll inputs need releasing then.
+    mouse_scroll_delta: (f32, f32),
+    pending_scroll_releases: Vec<InputEvent<Key>>,
+    // Vertical scrolling not yet taken by scroll_notches(), and
+    // how much makes a notch.
+    scroll_remainder: f32,
+    scroll_notch_size: f32,
+    // How much of an axis each pixel of mouse motion bound
+    // to it is worth.
+    mouse_sensitivities: HashMap<(MouseAxis, Axes), f64>,
+    // If set, the next key pressed gets bound to this
+    // instead of doing what it normally does.
+    pending_rebind: Option<InputEffect<Axes, Buttons>>,
+    // What the last rebound key was bound to before.
+    displaced_bindings: Vec<BoundEffect<Axes, Buttons>>,
+    // Whether a rebind hands the captured key's old binding the
+    // rebound effect's old key, and what got handed which key.
+    swap_on_conflict: bool,
+    swapped_bindings: Vec<(BoundEffect<Axes, Buttons>, Key)>,
+    // Which binding each held key triggered when it went down,
+    // so it releases the same one even if the modifiers change.
+    held_keys: HashMap<Key, InputEvent<Key>>,
+    // Every key that's physically down, bound or not
+    pressed_keys: HashSet<Key>,
+    // Where each controller hat was last pushed
+    hat_positions: HashMap<usize, HatPosition>,
+    // A key that scales what get_axis() reports for every axis
+    // while it's held, and by how much.
+    global_axis_scale: Option<(Key, f64)>,
+    // Keys whose bindings are being ignored for now
+    disabled_keys: HashSet<Key>,
+    all_keys_enabled: bool,
+    // Key combinations left for the OS or the game to deal with
+    reserved_combos: HashSet<(Key, Mod)>,
+    chords: Vec<Chord<Key, Buttons>>,
+    mouse_drags: Vec<MouseDrag<Axes>>,
+    // Keys that move an axis by a fixed step each time they're
+    // pressed, and the step.
+    stepped_keys: Vec<(Key, Axes, f64)>,
+    // Pairs of axes kept to a combined length of at most 1
+    linked_axes: Vec<(Axes, Axes)>,
+    sticks: Vec<AxisPair<Axes>>,
+    sequences: Vec<Sequence<Key, Buttons>>,
+    // The most recent key presses and when they happened, as
+    // many as the longest sequence needs.
+    recent_presses: VecDeque<(f64, Key)>,
+    // Total of all the dt's given to update() since the last
+    // reset_time()
+    time: f64,
+    // The dt update_frames() passes for each frame
+    fixed_timestep: f64,
+    // Key events captured since start_recording(), if recording
+    recording: Option<Vec<InputFrame<Key>>>,
+    recording_time: f64,
+    // Recorded key events still to be played back, if replaying
+    replay: Option<VecDeque<InputFrame<Key>>>,
+    replay_time: f64,
+    // While true, update() doesn't do anything.
+    paused: bool,
+    // While true, every axis jumps straight to where it's pushed.
+    instant_mode: bool,
+    press_callbacks: HashMap<Buttons, Vec<ButtonCallback>>,
+    release_callbacks: HashMap<Buttons, Vec<ButtonCallback>>,
+    // Typed text not taken yet, and whether to take it and
+    // ignore key bindings meanwhile.
+    text_input: String,
+    text_input_enabled: bool,
+    text_input_suppresses_keys: bool,
+    ignore_repeats: bool,
+    // Whether any bound input was pressed since the last update()
+    any_input: bool,
+    // What's happened since the last drain_events(), once
+    // that's been called.
+    events: Option<Vec<InputAction<Axes, Buttons>>>,
+    // Which axes and buttons encode_frame() packs, in order
+    frame_axes: Vec<Axes>,
+    frame_buttons: Vec<Buttons>,
 }
 
-impl<Axes, Buttons> InputManager<Axes, Buttons>
+impl<Axes, Buttons, Key> InputManager<Axes, Buttons, Key>
     where Axes: Eq + Hash + Clone,
-          Buttons: Eq + Hash + Clone
+          Buttons: Eq + Hash + Clone,
+          Key: Eq + Hash + Clone
 {
     pub fn new() -> Self {
+        InputManager::with_capacity(0, 0, 0)
+    }
+
+    /// Like `new()`, but with room for the given number of bound
+    /// inputs, axes and buttons set aside up front, to save growing
+    /// the tables while setting up lots of bindings.
+    pub fn with_capacity(bindings: usize, axes: usize, buttons: usize) -> Self {
         InputManager {
-            bindings: HashMap::new(),
-            axes: HashMap::new(),
-            buttons: HashMap::new(),
+            bindings: HashMap::with_capacity(bindings),
+            contexts: HashMap::new(),
+            context_stack: Vec::new(),
+            axes: HashMap::with_capacity(axes),
+            buttons: HashMap::with_capacity(buttons),
+            mouse_position: (0, 0),
+            last_mouse_position: (0, 0),
+            mouse_scroll_delta: (0.0, 0.0),
+            pending_scroll_releases: Vec::new(),
+            scroll_remainder: 0.0,
+            scroll_notch_size: 1.0,
+            mouse_sensitivities: HashMap::new(),
+            pending_rebind: None,
+            displaced_bindings: Vec::new(),
+            swap_on_conflict: false,
+            swapped_bindings: Vec::new(),
+            held_keys: HashMap::new(),
+            hat_positions: HashMap::new(),
+            pressed_keys: HashSet::new(),
+            global_axis_scale: None,
+            disabled_keys: HashSet::new(),
+            all_keys_enabled: true,
+            reserved_combos: HashSet::new(),
+            chords: Vec::new(),
+            mouse_drags: Vec::new(),
+            stepped_keys: Vec::new(),
+            linked_axes: Vec::new(),
+            sticks: Vec::new(),
+            sequences: Vec::new(),
+            recent_presses: VecDeque::new(),
+            time: 0.0,
+            fixed_timestep: 1.0 / 60.0,
+            recording: None,
+            recording_time: 0.0,
+            replay: None,
+            replay_time: 0.0,
+            paused: false,
+            instant_mode: false,
+            press_callbacks: HashMap::new(),
+            release_callbacks: HashMap::new(),
+            text_input: String::new(),
+            text_input_enabled: false,
+            text_input_suppresses_keys: true,
+            ignore_repeats: true,
+            any_input: false,
+            events: None,
+            frame_axes: Vec::new(),
+            frame_buttons: Vec::new(),
         }
     }
 
     /// Adds a key binding connecting the given keycode to the given
-    /// logical axis.
-    pub fn bind_key_to_axis(mut self, keycode: Keycode, axis: Axes, positive: bool) -> Self {
+    /// logical axis.  If the key is already bound to other things,
+    /// it will do this as well as them.
+    pub fn bind_key_to_axis(mut self, keycode: Key, axis: Axes, positive: bool) -> Self {
+        self.add_key_axis(keycode, axis, positive);
+        self
+    }
+
+    /// Like `bind_key_to_axis()`, but changes the manager in place
+    /// instead of taking and returning it, for adding bindings
+    /// after it's built, such as from a loop over a config file.
+    pub fn add_key_axis(&mut self, keycode: Key, axis: Axes, positive: bool) {
+        self.add_binding(InputEvent::KeyEvent(keycode, Mod::empty()),
+                         InputEffect::Axis(axis, if positive { 1.0 } else { -1.0 }));
+    }
+
+    /// Like `bind_key_to_axis()`, but holding the key only pushes the
+    /// axis as far as `magnitude` instead of all the way to 1.0; it
+    /// accelerates there and falls back as usual.
+    pub fn bind_key_to_axis_with_magnitude(self,
+                                           keycode: Key,
+                                           axis: Axes,
+                                           positive: bool,
+                                           magnitude: f64)
+                                           -> Self {
+        self.bind_key_mod_to_axis_with_magnitude(keycode, Mod::empty(), axis, positive, magnitude)
+    }
 
-        self.bindings.insert(InputEvent::KeyEvent(keycode),
-                             InputEffect::Axis(axis.clone(), positive));
-        self.axes.insert(axis, AxisStatus::default());
+    /// Binds the given key to push the axis toward `value`, from -1 to
+    /// 1, rather than all the way one way or the other.  `0.5` is the
+    /// same as `bind_key_to_axis_with_magnitude()` with `positive` true
+    /// and a magnitude of 0.5.  Values past -1 or 1 are clamped.
+    pub fn bind_key_to_axis_value(mut self, keycode: Key, axis: Axes, value: f64) -> Self {
+        let value = f64::max(-1.0, f64::min(1.0, value));
+        self.add_binding(InputEvent::KeyEvent(keycode, Mod::empty()),
+                         InputEffect::Axis(axis, value));
         self
     }
 
-    /// Adds a key binding connecting the given keycode to the given
-    /// logical button.
-    pub fn bind_key_to_button(mut self, keycode: Keycode, button: Buttons) -> Self {
-        self.bindings.insert(InputEvent::KeyEvent(keycode),
-                             InputEffect::Button(button.clone()));
-        self.buttons.insert(button, false);
+    /// Like `bind_key_mod_to_button()`, but for an axis.
+    pub fn bind_key_mod_to_axis(self, keycode: Key, mods: Mod, axis: Axes, positive: bool) -> Self {
+        self.bind_key_mod_to_axis_with_magnitude(keycode, mods, axis, positive, 1.0)
+    }
+
+    /// Like `bind_key_to_axis_with_magnitude()`, but only for when the
+    /// given modifier keys are held, such as for Shift and an arrow key
+    /// to walk slowly.  See `bind_key_mod_to_button()`.
+    pub fn bind_key_mod_to_axis_with_magnitude(mut self,
+                                               keycode: Key,
+                                               mods: Mod,
+                                               axis: Axes,
+                                               positive: bool,
+                                               magnitude: f64)
+                                               -> Self {
+        let value = if positive { magnitude } else { -magnitude };
+        self.add_binding(InputEvent::KeyEvent(keycode, mods), InputEffect::Axis(axis, value));
         self
     }
 
-    /// Updates the logical input state based on the actual
-    /// physical input state.  Should be called in your update()
-    /// handler.
-    /// So, it will do things like move the axes and so on.
-    pub fn update(&mut self, dt: f64) {
-        for (_axis, axis_status) in self.axes.iter_mut() {
-            if axis_status.direction != 0.0 {
-                // Accelerate the axis towards the
-                // input'ed direction.
-                let abs_dx = f64::min(axis_status.acceleration * dt,
-                                      1.0 - f64::abs(axis_status.position));
-                let dx = if axis_status.direction > 0.0 {
-                    abs_dx
-                } else {
-                    -abs_dx
-                };
-                axis_status.position += dx;
-            } else {
-                // Gravitate back towards 0.
-                let abs_dx = f64::min(axis_status.gravity * dt, f64::abs(axis_status.position));
-                let dx = if axis_status.position > 0.0 {
-                    -abs_dx
-                } else {
-                    abs_dx
-                };
-                axis_status.position += dx;
-            }
-        }
+    /// Like `bind_key_to_axis()`, but also sets how fast the axis
+    /// moves toward the pressed direction (`acceleration`) and back
+    /// toward 0 when released (`gravity`), in units per second.
+    /// This applies to the whole axis, not just this key.
+    pub fn bind_key_to_axis_with_tween(self,
+                                       keycode: Key,
+                                       axis: Axes,
+                                       positive: bool,
+                                       acceleration: f64,
+                                       gravity: f64)
+                                       -> Self {
+        let mut s = self.bind_key_to_axis(keycode, axis.clone(), positive);
+        s.set_axis_tween(axis, acceleration, gravity);
+        s
     }
 
-    /// This method should get called by your key_down_event handler.
-    pub fn update_keydown(&mut self, keycode: Option<Keycode>) {
-        if let Some(keycode) = keycode {
-            let effect = {
-                if let Some(e) = self.bindings.get(&InputEvent::KeyEvent(keycode)) {
-                    e.clone()
-                } else {
-                    return;
-                }
+    /// Like `bind_key_to_axis()`, but if `clamp_at_zero` is true the
+    /// axis also never goes past 0 the other way, even with another
+    /// key pushing it there, such as for a throttle with one pedal.
+    /// That's done by putting 0 at that end of the axis' range, so
+    /// it's the same as calling `set_axis_range()` with 0 for `min`
+    /// (or `max`), and applies to the whole axis.
+    pub fn bind_key_to_axis_clamped(self,
+                                    keycode: Key,
+                                    axis: Axes,
+                                    positive: bool,
+                                    clamp_at_zero: bool)
+                                    -> Self {
+        let mut s = self.bind_key_to_axis(keycode, axis.clone(), positive);
+        if clamp_at_zero {
+            let (min, max) = {
+                let axis_status = &s.axes[&axis];
+                (axis_status.min, axis_status.max)
             };
-            self.update_effect(effect, true);
+            if positive {
+                s.set_axis_range(axis, 0.0, max);
+            } else {
+                s.set_axis_range(axis, min, 0.0);
+            }
         }
+        s
     }
 
-    /// This method should get called by your key_up_event handler.
-    pub fn update_keyup(&mut self, keycode: Option<Keycode>) {
-        if let Some(keycode) = keycode {
-            let effect = {
-                if let Some(e) = self.bindings.get(&InputEvent::KeyEvent(keycode)) {
-                    e.clone()
-                } else {
-                    return;
-                }
-            };
-            self.update_effect(effect, false);
-        }
+    /// Makes each press of the given key move the axis by `delta`, such
+    /// as for picking gears or zoom levels, instead of pushing it.  It
+    /// stays within the axis' range, and the axis doesn't fall back,
+    /// so `get_axis()` is whichever step it's on.  The key can still be
+    /// bound to other things as usual.
+    pub fn bind_key_to_stepped_axis(mut self, keycode: Key, axis: Axes, delta: f64) -> Self {
+        self.set_axis_hold(axis.clone(), true);
+        self.stepped_keys.push((keycode, axis, delta));
+        self
     }
 
-    /// Takes an InputEffect and actually applies it.
-    fn update_effect(&mut self, effect: InputEffect<Axes, Buttons>, started: bool) {
-        match effect {
-            InputEffect::Axis(axis, direction) => {
-                let f = || AxisStatus::default();
-                let axis_status = self.axes.entry(axis).or_insert_with(f);
-                if started {
-                    let direction_float = if direction { 1.0 } else { -1.0 };
-                    axis_status.direction = direction_float;
-                } else {
-                    axis_status.direction = 0.0;
-                }
+    /// Moves the axes stepped by the given key.
+    fn step_axes(&mut self, keycode: &Key) {
+        for &(ref k, ref axis, delta) in &self.stepped_keys {
+            if k != keycode {
+                continue;
             }
-            InputEffect::Button(button) => {
-                let button_pressed = self.buttons.entry(button).or_insert(started);
-                *button_pressed = started;
-
+            if let Some(axis_status) = self.axes.get_mut(axis) {
+                let position = axis_status.clamped(axis_status.position + delta);
+                axis_status.analog = false;
+                axis_status.set_position(position);
+                self.any_input = true;
             }
         }
     }
 
-    pub fn get_axis(&mut self, axis: Axes) -> f64 {
-        let f = || AxisStatus::default();
-        let axis_status = self.axes.entry(axis).or_insert_with(f);
-        axis_status.position
+    /// Like `bind_key_to_axis()`, but for several keys at once.
+    pub fn bind_keys_to_axis(self, keys: &[Key], axis: Axes, positive: bool) -> Self {
+        keys.iter()
+            .fold(self, |s, key| s.bind_key_to_axis(key.clone(), axis.clone(), positive))
     }
 
-    pub fn get_axis_raw(&mut self, axis: Axes) -> f64 {
-        let f = || AxisStatus::default();
-        let axis_status = self.axes.entry(axis).or_insert_with(f);
-        axis_status.direction
+    /// Like `bind_key_to_button()`, but for several keys at once.
+    /// The button stays pressed as long as any of them are held.
+    pub fn bind_keys_to_button(self, keys: &[Key], button: Buttons) -> Self {
+        keys.iter().fold(self, |s, key| s.bind_key_to_button(key.clone(), button.clone()))
     }
 
-    pub fn get_button(&self, axis: Buttons) -> bool {
-        if let Some(pressed) = self.buttons.get(&axis) {
-            *pressed
-        } else {
-            false
-        }
+    /// Adds a key binding connecting the given keycode to the given
+    /// logical button.  If the key is already bound to other things,
+    /// it will do this as well as them.
+    pub fn bind_key_to_button(mut self, keycode: Key, button: Buttons) -> Self {
+        self.add_key_button(keycode, button);
+        self
     }
 
-    pub fn get_button_down(&self, axis: Buttons) -> bool {
-        self.get_button(axis)
+    /// Like `bind_key_to_button()`, but changes the manager in place.
+    /// See `add_key_axis()`.
+    pub fn add_key_button(&mut self, keycode: Key, button: Buttons) {
+        self.add_binding(InputEvent::KeyEvent(keycode, Mod::empty()),
+                         InputEffect::Button(button));
     }
 
-    pub fn get_button_up(&self, axis: Buttons) -> bool {
-        !self.get_button(axis)
+    /// Like `bind_key_to_button()`, but fails instead if the key
+    /// is already bound to something else, such as when loading
+    /// bindings from a config file the player wrote.  Binding a key
+    /// to a button it's already bound to is fine.
+    pub fn try_bind_key_to_button(&mut self,
+                                  keycode: Key,
+                                  button: Buttons)
+                                  -> Result<(), BindingConflict<Axes, Buttons>> {
+        let new = BoundEffect::Button(button.clone());
+        if let Some(existing) = self.bindings_for_key(keycode.clone())
+            .into_iter()
+            .find(|effect| *effect != new) {
+            return Err(BindingConflict { existing: existing });
+        }
+        self.add_key_button(keycode, button);
+        Ok(())
     }
 
-    pub fn mouse_position() {}
+    /// Adds a key binding connecting the given keycode to the given
+    /// logical button, that only triggers while all the given modifier
+    /// keys are held as well.  Note that SDL tells left and right
+    /// modifiers apart, so binding the left Ctrl modifier won't trigger
+    /// with the right one.
+    ///
+    /// When a key is pressed, only the most specific binding that
+    /// matches the held modifiers is used: the one with the most
+    /// modifiers, with plain key bindings coming last.  So if both S and
+    /// Ctrl+S are bound, pressing Ctrl+S does only what Ctrl+S is bound
+    /// to, while pressing S or Shift+S does what S is bound to.
+    pub fn bind_key_mod_to_button(mut self, keycode: Key, mods: Mod, button: Buttons) -> Self {
+        self.add_binding(InputEvent::KeyEvent(keycode, mods), InputEffect::Button(button));
+        self
+    }
 
-    pub fn mouse_scroll_delta() {}
+    /// Binds a chord of keys to the given logical button, so the
+    /// button is pressed only while all of the keys are held at once,
+    /// and released as soon as any of them is let go.  The keys
+    /// still do whatever else they're bound to as well.
+    pub fn bind_chord_to_button(mut self, keys: Vec<Key>, button: Buttons) -> Self {
+        if keys.is_empty() {
+            return self;
+        }
+        self.buttons.entry(button.clone()).or_insert_with(ButtonStatus::default);
+        self.chords.push(Chord {
+            keys: keys,
+            button: button,
+            active: false,
+        });
+        self
+    }
 
-    pub fn get_mouse_button() {}
+    /// Registers a sequence of keys that have to be pressed in
+    /// order, with no more than `window` seconds between each
+    /// press, such as for fighting game special moves.
+    /// `sequence_triggered()` will then be true for one frame when
+    /// the last key of the sequence is pressed.
+    ///
+    /// Sequences are named by a logical button value, which can be
+    /// one that's not bound to anything else.  Registering another
+    /// sequence with the same name replaces the old one.
+    pub fn register_sequence(&mut self, id: Buttons, steps: Vec<Key>, window: f64) {
+        self.sequences.retain(|seq| seq.id != id);
+        if steps.is_empty() {
+            return;
+        }
+        self.sequences.push(Sequence {
+            id: id,
+            steps: steps,
+            window: window,
+            triggered: false,
+        });
+    }
 
-    pub fn get_mouse_button_down() {}
+    /// Returns true if the given sequence was completed since
+    /// the last call to `update()`.
+    pub fn sequence_triggered(&self, id: Buttons) -> bool {
+        self.sequences.iter().any(|seq| seq.id == id && seq.triggered)
+    }
 
-    pub fn get_mouse_button_up() {}
+    /// Adds a binding connecting the given mouse button to the given
+    /// logical button.  The logical button can be bound to keys as
+    /// well, and stays pressed as long as any of them are held.
+    pub fn bind_mouse_to_button(mut self, button: MouseButton, logical: Buttons) -> Self {
+        self.add_binding(InputEvent::MouseButtonEvent(button),
+                         InputEffect::Button(logical));
+        self
+    }
 
-    pub fn reset_input_axes(&mut self) {
-        for (_axis, axis_status) in self.axes.iter_mut() {
-            axis_status.position = 0.0;
-            axis_status.direction = 0.0;
-        }
+    /// Adds a binding connecting scrolling the mouse wheel in the
+    /// given direction to the given logical button.  Each scroll
+    /// presses the button until the next `update()`.
+    pub fn bind_scroll_to_button(mut self, direction: ScrollDirection, logical: Buttons) -> Self {
+        self.add_binding(InputEvent::MouseScrollEvent(direction),
+                         InputEffect::Button(logical));
+        self
     }
-}
 
+    /// Adds a binding connecting scrolling the mouse wheel in the
+    /// given direction to the given logical axis.  Like a key, it
+    /// pushes the axis, but only until the next `update()`.
+    pub fn bind_scroll_to_axis(mut self,
+                               direction: ScrollDirection,
+                               axis: Axes,
+                               positive: bool)
+                               -> Self {
+        self.add_binding(InputEvent::MouseScrollEvent(direction),
+                         InputEffect::Axis(axis, if positive { 1.0 } else { -1.0 }));
+        self
+    }
 
-#[cfg(test)]
-mod tests {
-    use ggez::event::*;
-    use super::*;
+    /// Adds a binding connecting movement of the mouse along the given
+    /// direction to the given logical axis, for things like free-look
+    /// cameras.  Each pixel moved pushes the axis by 0.01; see
+    /// `bind_mouse_motion_to_axis_with_sensitivity()`.
+    pub fn bind_mouse_motion_to_axis(self, mouse_axis: MouseAxis, logical: Axes) -> Self {
+        self.bind_mouse_motion_to_axis_with_sensitivity(mouse_axis, logical, 0.01)
+    }
 
-    #[derive(Hash, Eq, PartialEq, Copy, Clone, Debug)]
-    enum Buttons {
-        A,
-        B,
-        Select,
-        Start,
+    /// Like `bind_mouse_motion_to_axis()`, but each pixel moved pushes
+    /// the axis by `sensitivity`.  The axis position jumps by that
+    /// much when the mouse moves, without going past the ends of the
+    /// axis, and since mouse motion is relative it falls back toward
+    /// 0 at the axis' gravity afterward.
+    pub fn bind_mouse_motion_to_axis_with_sensitivity(mut self,
+                                                      mouse_axis: MouseAxis,
+                                                      logical: Axes,
+                                                      sensitivity: f64)
+                                                      -> Self {
+        self.mouse_sensitivities.insert((mouse_axis, logical.clone()), sensitivity);
+        self.add_binding(InputEvent::MouseMotionEvent(mouse_axis),
+                         InputEffect::Axis(logical, 1.0));
+        self
     }
 
-    #[derive(Hash, Eq, PartialEq, Copy, Clone, Debug)]
-    enum Axes {
-        Horz,
-        Vert,
+    /// Makes dragging with the given mouse button move a pair of axes:
+    /// while it's held, they're set to how far the mouse is from where
+    /// the button went down, `pixels_per_unit` pixels to 1.0 and kept
+    /// within the ends of the axes.  As with mouse coordinates, +Y is
+    /// down.  Releasing the button puts both axes back to 0.
+    pub fn bind_mouse_drag_to_axes(mut self,
+                                   button: MouseButton,
+                                   x_axis: Axes,
+                                   y_axis: Axes,
+                                   pixels_per_unit: f64)
+                                   -> Self {
+        self.axes.entry(x_axis.clone()).or_insert_with(AxisStatus::default);
+        self.axes.entry(y_axis.clone()).or_insert_with(AxisStatus::default);
+        self.mouse_drags.push(MouseDrag {
+            button: button,
+            x_axis: x_axis,
+            y_axis: y_axis,
+            pixels_per_unit: pixels_per_unit,
+            origin: None,
+        });
+        self
     }
-    #[test]
-    fn test_input_events() {
+
+    /// Adds a binding connecting the given button on the game
+    /// controller with the given id to the given logical button.
+    pub fn bind_controller_to_button(mut self,
+                                     controller: usize,
+                                     btn: Button,
+                                     logical: Buttons)
+                                     -> Self {
+        self.add_binding(InputEvent::ControllerButtonEvent(controller, btn),
+                         InputEffect::Button(logical));
+        self
+    }
+
+    /// Adds a binding connecting the given stick or trigger axis on
+    /// the game controller with the given id to the given logical axis.
+    pub fn bind_controller_axis_to_axis(mut self,
+                                        controller: usize,
+                                        controller_axis: Axis,
+                                        logical: Axes)
+                                        -> Self {
+        self.add_binding(InputEvent::ControllerAxisEvent(controller, controller_axis),
+                         InputEffect::Axis(logical, 1.0));
+        self
+    }
+
+    /// Adds bindings making the controller hat with the given id push
+    /// the given axes the way arrow keys would: right and up push them
+    /// positive, left and down negative, and the diagonals push both.
+    pub fn bind_controller_hat_to_axes(mut self, hat: usize, x_axis: Axes, y_axis: Axes) -> Self {
+        let directions = [(HatPosition::Right, x_axis.clone(), 1.0),
+                          (HatPosition::Left, x_axis, -1.0),
+                          (HatPosition::Up, y_axis.clone(), 1.0),
+                          (HatPosition::Down, y_axis, -1.0)];
+        for &(direction, ref axis, value) in directions.iter() {
+            self.add_binding(InputEvent::ControllerHatEvent(hat, direction),
+                             InputEffect::Axis(axis.clone(), value));
+        }
+        self
+    }
+
+    /// Adds bindings connecting both axes of the given stick on the
+    /// game controller with the given id to the given logical axes, so
+    /// they can be read together with `get_stick()`.
+    pub fn bind_stick(self, controller: usize, stick: StickId, x_axis: Axes, y_axis: Axes) -> Self {
+        let (stick_x, stick_y) = match stick {
+            StickId::Left => (Axis::LeftX, Axis::LeftY),
+            StickId::Right => (Axis::RightX, Axis::RightY),
+        };
+        self.bind_controller_axis_to_axis(controller, stick_x, x_axis)
+            .bind_controller_axis_to_axis(controller, stick_y, y_axis)
+    }
+
+    /// Makes one input manager per player for local multiplayer,
+    /// calling `layout` with a new manager and each player's number
+    /// from 0 up to set up the bindings.  Binding controller `player`
+    /// in `layout` gives each player their own controller; the events
+    /// from a controller can then be given to every manager, and only
+    /// the one with that controller bound responds.
+    ///
+    /// Keyboard and mouse events don't say where they came from, so
+    /// each manager responds to whatever keys and mouse buttons it has
+    /// bound.  Either only bind them for one player, or give each
+    /// player different keys, and pass the events to all managers.
+    pub fn per_player<F>(players: usize, layout: F) -> Vec<Self>
+        where F: Fn(Self, usize) -> Self
+    {
+        (0..players).map(|player| layout(InputManager::new(), player)).collect()
+    }
+
+    /// Sets how fast the given axis moves toward the pressed direction
+    /// and falls back to 0, in units per second.  Defaults are 4.0
+    /// and 3.0 respectively.  Infinity makes it get there at once.
+    pub fn set_axis_tween(&mut self, axis: Axes, acceleration: f64, gravity: f64) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.acceleration = acceleration;
+        axis_status.negative_acceleration = acceleration;
+        axis_status.gravity = gravity;
+    }
+
+    /// Like `set_axis_tween()`, but in frames at the given framerate
+    /// to get from 0 to 1 and back, which can be easier to tune by
+    /// feel.  See `AxisTween::from_frames()`.  Returns false, and
+    /// leaves the axis alone, if those aren't valid.
+    pub fn set_axis_tween_frames(&mut self,
+                                 axis: Axes,
+                                 accel_frames: f64,
+                                 gravity_frames: f64,
+                                 fps: f64)
+                                 -> bool {
+        match AxisTween::from_frames(accel_frames, gravity_frames, fps) {
+            Some(tween) => {
+                self.set_axis_tween(axis, tween.acceleration, tween.gravity);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Like `set_axis_tween()`, but with a different acceleration
+    /// for each way the axis can be pushed, such as a throttle that
+    /// revs up slowly and cuts quickly.
+    pub fn set_axis_asymmetric(&mut self,
+                               axis: Axes,
+                               accel_pos: f64,
+                               accel_neg: f64,
+                               gravity: f64) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.acceleration = accel_pos;
+        axis_status.negative_acceleration = accel_neg;
+        axis_status.gravity = gravity;
+    }
+
+    /// Makes `update()` keep the positions of the two axes within the
+    /// unit circle, as an X and Y axis, so moving diagonally with keys
+    /// isn't faster than moving straight.  This changes where the axes
+    /// actually are, unlike `get_axes_vector_normalized()`, so the
+    /// velocity is of the shortened position too.
+    pub fn link_axes_as_vector(&mut self, x_axis: Axes, y_axis: Axes) {
+        self.axes.entry(x_axis.clone()).or_insert_with(AxisStatus::default);
+        self.axes.entry(y_axis.clone()).or_insert_with(AxisStatus::default);
+        self.linked_axes.push((x_axis, y_axis));
+    }
+
+    /// Sets the radial deadzone `get_stick()` uses for the given pair
+    /// of axes.  Unlike `set_axis_deadzone()`, which cuts off each axis
+    /// on its own and so makes a square, this goes by how far the
+    /// stick is pushed overall, so a small push diagonally is just as
+    /// dead as a small push straight.  Defaults to 0.
+    pub fn set_stick_deadzone(&mut self, x_axis: Axes, y_axis: Axes, deadzone: f64) {
+        let existing = self.sticks
+            .iter()
+            .position(|pair| pair.x_axis == x_axis && pair.y_axis == y_axis);
+        match existing {
+            Some(i) => self.sticks[i].deadzone = deadzone,
+            None => {
+                self.sticks.push(AxisPair {
+                    x_axis: x_axis,
+                    y_axis: y_axis,
+                    deadzone: deadzone,
+                })
+            }
+        }
+    }
+
+    /// Shortens each pair of linked axes that's longer than 1.
+    fn normalize_linked_axes(&mut self) {
+        for &(ref x_axis, ref y_axis) in &self.linked_axes {
+            let x = self.axes[x_axis].position;
+            let y = self.axes[y_axis].position;
+            let length = f64::sqrt(x * x + y * y);
+            if length > 1.0 {
+                self.axes.get_mut(x_axis).unwrap().scale_position(1.0 / length);
+                self.axes.get_mut(y_axis).unwrap().scale_position(1.0 / length);
+            }
+        }
+    }
+
+    /// Makes the given axis spring back to rest when nothing's pushing
+    /// it, instead of falling back at its gravity, for a joystick-like
+    /// feel.  With little `damping` for the `stiffness` it overshoots
+    /// and wobbles a bit before settling; with more it eases back
+    /// without overshooting.  It always ends up exactly at rest.
+    pub fn set_axis_spring(&mut self, axis: Axes, stiffness: f64, damping: f64) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.spring = Some((stiffness, damping));
+        axis_status.spring_velocity = 0.0;
+    }
+
+    /// Sets the deadzone for the given axis; `get_axis()` returns
+    /// 0 while the axis is closer to 0 than this.  `get_axis_raw()`
+    /// is not affected.  Defaults to 0.
+    pub fn set_axis_deadzone(&mut self, axis: Axes, deadzone: f64) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.deadzone = deadzone;
+    }
+
+    /// Sets whether `get_axis()` reports the given axis flipped,
+    /// for things like inverted look controls.  The axis itself
+    /// moves the same either way, and `get_axis_raw()` is not
+    /// affected.  Axes start out uninverted.
+    pub fn set_axis_inverted(&mut self, axis: Axes, inverted: bool) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.inverted = inverted;
+    }
+
+    /// Sets a multiplier that `get_axis()` scales the given axis by,
+    /// for things like mouse-look sensitivity.  Unlike the acceleration
+    /// this doesn't change how the axis moves, only what it reports.
+    /// Defaults to 1.0.
+    pub fn set_axis_sensitivity(&mut self, axis: Axes, scale: f64) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.sensitivity = scale;
+    }
+
+    /// Sets whether the scaled value from `set_axis_sensitivity()`
+    /// gets clamped back to [-1, 1].  Defaults to false.
+    pub fn set_axis_sensitivity_clamped(&mut self, axis: Axes, clamp: bool) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.clamp_sensitivity = clamp;
+    }
+
+    /// Sets whether the given axis snaps straight to -1, 0 or +1
+    /// as keys are pressed and released, skipping the acceleration
+    /// and gravity entirely.  Defaults to false.
+    pub fn set_axis_snap(&mut self, axis: Axes, snap: bool) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.snap = snap;
+    }
+
+    /// Sets how key and analog inputs bound to the same axis are
+    /// combined.  By default it's `AxisBlend::LastWins`: an analog
+    /// input sets the axis position, and a key pressed after that
+    /// takes over and tweens it from there.  With the others, the keys
+    /// tween the axis the same as if there was no analog input, and
+    /// that gets combined with the last analog value.
+    pub fn set_axis_blend(&mut self, axis: Axes, blend: AxisBlend) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.blend = blend;
+        axis_status.combine_sources();
+    }
+
+    /// Sets whether the given axis stays where it is when released
+    /// instead of falling back to 0, like a zoom level that's moved
+    /// with a pair of keys.  Defaults to false.
+    pub fn set_axis_hold(&mut self, axis: Axes, hold: bool) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.hold = hold;
+    }
+
+    /// Sets the easing curve the given axis follows as it accelerates
+    /// toward its direction and falls back to 0; the acceleration and
+    /// gravity set how long that takes, and the curve sets its shape.
+    /// Defaults to `AxisCurve::Linear`.
+    pub fn set_axis_curve(&mut self, axis: Axes, curve: AxisCurve) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        let position = axis_status.position;
+        axis_status.curve = curve;
+        axis_status.set_position(position);
+    }
+
+    /// Sets the range the given axis moves in.  Pushing it positive
+    /// moves it toward `max` and negative toward `min`, and with
+    /// nothing pushing it it falls back to 0, or whichever end of
+    /// the range is closest to 0.  So a range of [0, 1] makes a
+    /// one-way axis like a throttle.  Defaults to [-1, 1].
+    pub fn set_axis_range(&mut self, axis: Axes, min: f64, max: f64) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.min = min;
+        axis_status.max = max;
+        let position = f64::max(min, f64::min(max, axis_status.position));
+        axis_status.set_position(position);
+    }
+
+    /// Sets whether the given axis is kept within its range.  When it
+    /// isn't, keys and mouse motion keep pushing it further for as
+    /// long as they're held, which is handy for axes that total up
+    /// input, like how far the mouse has moved altogether.  Gravity
+    /// still pulls it back to rest at the same speed however far out
+    /// it is, so it can take a while; see `set_axis_hold()` to stop
+    /// that.  Analog input is clamped regardless.  Defaults to true.
+    pub fn set_axis_clamp(&mut self, axis: Axes, clamp: bool) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.clamp = clamp;
+        let position = axis_status.clamped(axis_status.position);
+        axis_status.set_position(position);
+    }
+
+    /// Sets where the given axis rests, such as for a camera zoom axis
+    /// that starts out half way, and moves it there.  Gravity pulls it
+    /// back to this instead of 0, and `reset_axis()` puts it here.  If
+    /// it's outside the axis' range, the nearest end is used instead.
+    pub fn set_axis_initial(&mut self, axis: Axes, value: f64) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.initial = value;
+        let rest = axis_status.rest();
+        axis_status.set_position(rest);
+    }
+
+    /// Sets how far from 0 being pushed takes the given axis, such as
+    /// for a walk modifier that holds movement to half speed.  The axis
+    /// still accelerates as before, but stops at `max` or `-max`, unless
+    /// the end of its range is closer.  Gravity and the range work as
+    /// usual.  Use infinity to turn this off, which is the default.
+    pub fn set_axis_max(&mut self, axis: Axes, max: f64) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.max_magnitude = max;
+    }
+
+    /// Sets how close to its rest position the given axis has to get
+    /// for `axis_at_rest()` to say it's settled.  The default is 0.001.
+    pub fn set_axis_rest_tolerance(&mut self, axis: Axes, tolerance: f64) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.rest_tolerance = tolerance;
+    }
+
+    /// Makes the context with the given name the active one, until
+    /// `pop_context()` is called.  A context is a separate set of
+    /// bindings, such as for a menu or dialog box; while it's active,
+    /// its bindings are used first, and all the bind, unbind and query
+    /// methods work on it instead of the base bindings.  A context's
+    /// bindings are kept after it's popped, so pushing it again later
+    /// brings them back.
+    ///
+    /// If `opaque` is true, only the context's bindings are used.
+    /// Otherwise inputs it doesn't bind fall through to the context
+    /// below it on the stack, and so on down to the base bindings or
+    /// the first opaque context, so a dialog can bind just Confirm and
+    /// Cancel and leave movement working.
+    ///
+    /// Axis and button states are shared by all contexts.  Anything
+    /// held down when the context changes is released, so that nothing
+    /// gets stuck.
+    pub fn push_context(&mut self, name: &str, opaque: bool) {
+        self.release_all_inputs();
+        self.contexts.entry(name.to_string()).or_insert_with(HashMap::new);
+        self.context_stack.push((name.to_string(), opaque));
+    }
+
+    /// Goes back to the context that was active before the last
+    /// `push_context()`, returning the name of the one removed.
+    pub fn pop_context(&mut self) -> Option<String> {
+        self.release_all_inputs();
+        self.context_stack.pop().map(|(name, _)| name)
+    }
+
+    /// Returns the name of the active context, or None if
+    /// the base bindings are being used.
+    pub fn active_context(&self) -> Option<&str> {
+        self.context_stack.last().map(|&(ref name, _)| name.as_str())
+    }
+
+    fn active_bindings(&self) -> &BindingMap<Axes, Buttons, Key> {
+        match self.context_stack.last() {
+            Some(&(ref name, _)) => &self.contexts[name],
+            None => &self.bindings,
+        }
+    }
+
+    fn active_bindings_mut(&mut self) -> &mut BindingMap<Axes, Buttons, Key> {
+        match self.context_stack.last() {
+            Some(&(ref name, _)) => self.contexts.get_mut(name).unwrap(),
+            None => &mut self.bindings,
+        }
+    }
+
+    /// The sets of bindings inputs are looked up in, from the top of
+    /// the context stack down to the first opaque one.
+    fn visible_bindings(&self) -> Vec<&BindingMap<Axes, Buttons, Key>> {
+        let mut layers = Vec::new();
+        for &(ref name, opaque) in self.context_stack.iter().rev() {
+            layers.push(&self.contexts[name]);
+            if opaque {
+                return layers;
+            }
+        }
+        layers.push(&self.bindings);
+        layers
+    }
+
+    /// Finds what the given input is bound to in the topmost visible
+    /// context that binds it.
+    fn lookup_effects(&self, event: &InputEvent<Key>) -> Option<&Vec<InputEffect<Axes, Buttons>>> {
+        self.visible_bindings().into_iter().filter_map(|layer| layer.get(event)).next()
+    }
+
+    /// Lets go of everything that the held keys are doing and of
+    /// all axis inputs, without forgetting that the keys are held.
+    fn release_all_inputs(&mut self) {
+        let held: Vec<InputEvent<Key>> = self.held_keys.values().cloned().collect();
+        for event in held {
+            self.update_event(event, false);
+        }
+        for (_axis, axis_status) in self.axes.iter_mut() {
+            axis_status.positive_inputs.clear();
+            axis_status.negative_inputs.clear();
+            axis_status.update_direction();
+        }
+    }
+
+    /// Returns what the given key (without modifiers) is currently
+    /// bound to, if anything.
+    /// If it's bound to several things, this is the first one it
+    /// was bound to; see `bindings_for_key()` for all of them.
+    pub fn binding_for_key(&self, keycode: Key) -> Option<BoundEffect<Axes, Buttons>> {
+        self.bindings_for_key(keycode).into_iter().next()
+    }
+
+    /// Returns everything the given key (without modifiers) is bound to,
+    /// in the order the bindings were added.
+    pub fn bindings_for_key(&self, keycode: Key) -> Vec<BoundEffect<Axes, Buttons>> {
+        match self.lookup_effects(&InputEvent::KeyEvent(keycode, Mod::empty())) {
+            Some(effects) => effects.iter().cloned().map(BoundEffect::from).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Iterates over all plain key bindings (ones without modifiers)
+    /// and what they're bound to, in no particular order.  A key bound
+    /// to several things shows up once for each of them.
+    pub fn bindings_iter<'a>(&'a self)
+                             -> impl Iterator<Item = (Key, BoundEffect<Axes, Buttons>)> + 'a {
+        self.bindings_with_mods_iter()
+            .filter(|&(_, mods, _)| mods.is_empty())
+            .map(|(keycode, _, effect)| (keycode, effect))
+    }
+
+    /// Like `bindings_iter()`, but includes bindings with modifiers.
+    pub fn bindings_with_mods_iter<'a>
+        (&'a self)
+         -> impl Iterator<Item = (Key, Mod, BoundEffect<Axes, Buttons>)> + 'a {
+        self.active_bindings()
+            .iter()
+            .filter_map(|(event, effects)| match *event {
+                InputEvent::KeyEvent(ref keycode, mods) => Some((keycode, mods, effects)),
+                _ => None,
+            })
+            .flat_map(|(keycode, mods, effects)| {
+                effects.iter()
+                    .map(move |effect| (keycode.clone(), mods, BoundEffect::from(effect.clone())))
+            })
+    }
+
+    /// Returns true if anything in the current bindings presses the
+    /// given button, counting chords, such as for checking a loaded
+    /// keymap has everything the game needs.
+    pub fn has_binding_for_button(&self, button: Buttons) -> bool {
+        self.chords.iter().any(|chord| chord.button == button) ||
+        self.active_bindings().values().any(|effects| {
+            effects.iter().any(|effect| *effect == InputEffect::Button(button.clone()))
+        })
+    }
+
+    /// Like `has_binding_for_button()`, but for an axis being moved
+    /// either way, counting mouse drags.
+    pub fn has_binding_for_axis(&self, axis: Axes) -> bool {
+        self.mouse_drags.iter().any(|drag| drag.x_axis == axis || drag.y_axis == axis) ||
+        self.active_bindings().values().any(|effects| {
+            effects.iter().any(|effect| match *effect {
+                InputEffect::Axis(ref bound, _) => *bound == axis,
+                _ => false,
+            })
+        })
+    }
+
+    /// Lists every axis and button that has something bound to it but
+    /// hasn't been read since the manager was made or
+    /// `reset_usage_tracking()` was last called, in no particular
+    /// order.  Handy for catching a key bound to the wrong thing.
+    /// Reading means `get_axis()`, `get_axis_raw()`, `get_button()`,
+    /// `get_button_pressed()`, `get_button_released()`,
+    /// `get_double_tap()`, or anything built on them.  Bindings in every
+    /// context count, whether or not it's active.
+    pub fn unused_actions(&self) -> Vec<Action<Axes, Buttons>> {
+        let axes = self.axes
+            .iter()
+            .filter(|&(axis, status)| {
+                let dragged = self.mouse_drags
+                    .iter()
+                    .any(|drag| drag.x_axis == *axis || drag.y_axis == *axis);
+                let bound = dragged || self.any_layer_binds(|effect| match *effect {
+                    InputEffect::Axis(ref bound, _) => bound == axis,
+                    _ => false,
+                });
+                !status.queried.get() && bound
+            })
+            .map(|(axis, _)| Action::Axis(axis.clone()));
+        let buttons = self.buttons
+            .iter()
+            .filter(|&(button, status)| {
+                let effect = InputEffect::Button(button.clone());
+                let bound = self.chords.iter().any(|chord| chord.button == *button) ||
+                            self.any_layer_binds(|e| *e == effect);
+                !status.queried.get() && bound
+            })
+            .map(|(button, _)| Action::Button(button.clone()));
+        axes.chain(buttons).collect()
+    }
+
+    /// Whether anything in the base bindings or any context is bound
+    /// to an effect matching the given test.
+    fn any_layer_binds<F>(&self, test: F) -> bool
+        where F: Fn(&InputEffect<Axes, Buttons>) -> bool
+    {
+        ::std::iter::once(&self.bindings)
+            .chain(self.contexts.values())
+            .any(|layer| layer.values().any(|effects| effects.iter().any(&test)))
+    }
+
+    /// Starts tracking which axes and buttons get read over again,
+    /// for `unused_actions()`.
+    pub fn reset_usage_tracking(&mut self) {
+        for axis_status in self.axes.values() {
+            axis_status.queried.set(false);
+        }
+        for button_status in self.buttons.values() {
+            button_status.queried.set(false);
+        }
+    }
+
+    /// Adds a binding, creating state for its axis or button
+    /// if there isn't any yet.  Does nothing if the input is
+    /// already bound to exactly this.
+    fn add_binding(&mut self, event: InputEvent<Key>, effect: InputEffect<Axes, Buttons>) {
+        match effect {
+            InputEffect::Axis(ref axis, _) => {
+                self.axes.entry(axis.clone()).or_insert_with(AxisStatus::default);
+            }
+            InputEffect::Button(ref button) => {
+                self.buttons.entry(button.clone()).or_insert_with(ButtonStatus::default);
+            }
+        }
+        let effects = self.active_bindings_mut().entry(event).or_insert_with(Vec::new);
+        if !effects.contains(&effect) {
+            effects.push(effect);
+        }
+    }
+
+    /// Removes all the bindings the given keycode has, not counting
+    /// ones with modifiers.
+    /// The axes or buttons it was bound to keep their state, even if
+    /// nothing else is bound to them anymore, but if the key was
+    /// held down it stops pushing on its axes.
+    pub fn unbind_key(&mut self, keycode: Key) {
+        self.remove_binding(InputEvent::KeyEvent(keycode, Mod::empty()));
+    }
+
+    /// Like `unbind_key()`, but for the binding of the key with
+    /// the given modifiers.
+    pub fn unbind_key_mod(&mut self, keycode: Key, mods: Mod) {
+        self.remove_binding(InputEvent::KeyEvent(keycode, mods));
+    }
+
+    /// Makes the given key (without modifiers) do the given thing
+    /// instead of whatever it did before, such as for applying an
+    /// accessibility profile over the usual bindings.  Returns what it
+    /// was bound to, if anything; if that was several things, they're
+    /// all replaced, and this returns the first as `binding_for_key()`
+    /// would.  Like `unbind_key()`, a held key
+    /// stops pushing whatever it was bound to before.
+    pub fn set_key_effect(&mut self,
+                          keycode: Key,
+                          effect: BoundEffect<Axes, Buttons>)
+                          -> Option<BoundEffect<Axes, Buttons>> {
+        let previous = self.binding_for_key(keycode.clone());
+        self.unbind_key(keycode.clone());
+        self.add_binding(InputEvent::KeyEvent(keycode, Mod::empty()), effect.into());
+        previous
+    }
+
+    fn remove_binding(&mut self, event: InputEvent<Key>) {
+        for effect in self.active_bindings_mut().remove(&event).unwrap_or_default() {
+            if let InputEffect::Axis(axis, _) = effect {
+                if let Some(axis_status) = self.axes.get_mut(&axis) {
+                    axis_status.positive_inputs.remove(&event);
+                    axis_status.negative_inputs.remove(&event);
+                    axis_status.update_direction();
+                }
+            }
+        }
+    }
+
+    /// Moves the given button to just the given key: every key bound
+    /// to the button, with or without modifiers, stops pressing it,
+    /// and the new key is bound to it instead.  Anything else the old
+    /// keys do is left alone.  Returns the keys that were bound to it.
+    pub fn rebind_button(&mut self, button: Buttons, new_key: Key) -> Vec<Key> {
+        let effect = InputEffect::Button(button);
+        let old_keys = self.unbind_effect_from_keys(&effect);
+        self.add_binding(InputEvent::KeyEvent(new_key, Mod::empty()), effect);
+        old_keys
+    }
+
+    /// Removes the given effect from every key binding it's in,
+    /// releasing it, and returns the keys it was bound to.
+    fn unbind_effect_from_keys(&mut self, effect: &InputEffect<Axes, Buttons>) -> Vec<Key> {
+        let events: Vec<InputEvent<Key>> = self.active_bindings()
+            .iter()
+            .filter(|&(event, effects)| match *event {
+                InputEvent::KeyEvent(..) => effects.contains(effect),
+                _ => false,
+            })
+            .map(|(event, _)| event.clone())
+            .collect();
+        let mut old_keys = Vec::new();
+        for event in events {
+            self.update_effect(event.clone(), effect.clone(), false);
+            let now_unbound = {
+                let effects = self.active_bindings_mut().get_mut(&event).unwrap();
+                effects.retain(|e| e != effect);
+                effects.is_empty()
+            };
+            if now_unbound {
+                self.active_bindings_mut().remove(&event);
+            }
+            if let InputEvent::KeyEvent(keycode, _) = event {
+                if !old_keys.contains(&keycode) {
+                    old_keys.push(keycode);
+                }
+            }
+        }
+        old_keys
+    }
+
+    /// Removes all bindings.  Like `unbind_key()`, this leaves the
+    /// axis and button states alone other than releasing any
+    /// held axis inputs.
+    pub fn clear_bindings(&mut self) {
+        self.active_bindings_mut().clear();
+        for (_axis, axis_status) in self.axes.iter_mut() {
+            axis_status.positive_inputs.clear();
+            axis_status.negative_inputs.clear();
+            axis_status.update_direction();
+        }
+        self.held_keys.clear();
+    }
+
+    /// Replaces all the key bindings with the given ones, one effect
+    /// per key, such as from a config file.  Mouse and controller
+    /// bindings are left alone.
+    pub fn apply_keymap(&mut self, map: HashMap<Key, BoundEffect<Axes, Buttons>>) {
+        self.remove_key_bindings();
+        for (keycode, effect) in map {
+            self.add_binding(InputEvent::KeyEvent(keycode, Mod::empty()), effect.into());
+        }
+    }
+
+    /// Removes all the bindings of keys, with or without modifiers.
+    fn remove_key_bindings(&mut self) {
+        let old_keys: Vec<InputEvent<Key>> = self.active_bindings()
+            .keys()
+            .filter(|event| match **event {
+                InputEvent::KeyEvent(..) => true,
+                _ => false,
+            })
+            .cloned()
+            .collect();
+        for event in old_keys {
+            self.remove_binding(event);
+        }
+    }
+
+    /// Returns the plain key bindings as a map that can be given back
+    /// to `apply_keymap()`.  Keys bound to several things only get the
+    /// first, as with `binding_for_key()`, and bindings with modifiers
+    /// are left out; see `export_bindings()` to keep everything.
+    pub fn export_keymap(&self) -> HashMap<Key, BoundEffect<Axes, Buttons>> {
+        self.active_bindings()
+            .iter()
+            .filter_map(|(event, effects)| match (event, effects.first()) {
+                (&InputEvent::KeyEvent(ref keycode, mods), Some(effect)) if mods.is_empty() => {
+                    Some((keycode.clone(), BoundEffect::from(effect.clone())))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Turns all the bindings of the given key on or off without
+    /// removing them.  While a key is disabled, pressing it does
+    /// nothing.  If it's held down when it gets disabled, it's
+    /// released, so axes it was pushing stop moving.
+    pub fn set_binding_enabled(&mut self, keycode: Key, enabled: bool) {
+        if enabled {
+            self.disabled_keys.remove(&keycode);
+        } else {
+            self.disabled_keys.insert(keycode.clone());
+            self.release_key(&keycode);
+        }
+    }
+
+    /// Like `set_binding_enabled()`, but for every key at once.
+    /// This is separate from the per-key setting, so keys disabled
+    /// one at a time stay disabled after re-enabling them all.
+    pub fn set_all_enabled(&mut self, enabled: bool) {
+        self.all_keys_enabled = enabled;
+        if !enabled {
+            let held: Vec<Key> = self.held_keys.keys().cloned().collect();
+            for keycode in held {
+                self.release_key(&keycode);
+            }
+        }
+    }
+
+    /// Keeps the given key, pressed with (at least) the given
+    /// modifiers, from doing anything, such as Alt+F4 or an
+    /// Alt+Enter fullscreen toggle that shouldn't also fire whatever
+    /// Enter is bound to.  `update_keydown()` returns false for it, so
+    /// the game knows to handle it itself.  The key still works as
+    /// usual without those modifiers, and it's not picked up by
+    /// `begin_rebind()` either.
+    pub fn reserve_combo(&mut self, keycode: Key, mods: Mod) {
+        self.reserved_combos.insert((keycode, mods));
+    }
+
+    fn is_reserved(&self, keycode: &Key, keymod: Mod) -> bool {
+        self.reserved_combos.iter().any(|&(ref k, mods)| k == keycode && keymod.contains(mods))
+    }
+
+    fn key_enabled(&self, keycode: &Key) -> bool {
+        self.all_keys_enabled && !self.disabled_keys.contains(keycode)
+    }
+
+    /// Lets go of the given key if it's held down.
+    fn release_key(&mut self, keycode: &Key) {
+        if let Some(event) = self.held_keys.remove(keycode) {
+            self.update_event(event, false);
+            self.update_chords();
+        }
+    }
+
+    /// Updates the logical input state based on the actual
+    /// physical input state.  Should be called in your update()
+    /// handler.
+    /// So, it will do things like move the axes and so on.
+    pub fn update(&mut self, dt: f64) {
+        if self.paused {
+            return;
+        }
+        // Time never runs backwards, even if someone hands us a
+        // negative dt.
+        let dt = dt.max(0.0);
+        let instant = self.instant_mode;
+        for (_axis, axis_status) in self.axes.iter_mut() {
+            if instant && !axis_status.analog {
+                // Held axes stay where they were left, as usual.
+                if !(axis_status.hold && axis_status.direction == 0.0) {
+                    let target = axis_status.snap_target();
+                    axis_status.set_position(target);
+                }
+            } else {
+                axis_status.tween(dt);
+            }
+        }
+        self.normalize_linked_axes();
+        for (axis, axis_status) in self.axes.iter_mut() {
+            if axis_status.position != axis_status.last_position {
+                if let Some(ref mut events) = self.events {
+                    events.push(InputAction::AxisChanged {
+                        axis: axis.clone(),
+                        value: axis_status.output(),
+                    });
+                }
+            }
+            axis_status.velocity = if dt > 0.0 {
+                (axis_status.position - axis_status.last_position) / dt
+            } else {
+                0.0
+            };
+            axis_status.last_position = axis_status.position;
+            if axis_status.saturated() {
+                axis_status.saturation_count += 1;
+            }
+            if axis_status.history_samples > 0 {
+                if axis_status.history.len() == axis_status.history_samples {
+                    axis_status.history.remove(0);
+                }
+                axis_status.history.push(axis_status.position);
+            }
+        }
+        for (button, button_status) in self.buttons.iter_mut() {
+            button_status.pressed_edge = false;
+            button_status.released_edge = false;
+            button_status.double_tapped = false;
+            if button_status.pressed {
+                button_status.held_time += dt;
+            }
+            if let Some(ref mut t) = button_status.since_press {
+                *t += dt;
+            }
+            if let Some(ref mut t) = button_status.since_release {
+                *t += dt;
+            }
+            if button_status.pressed && button_status.oneshot > 0.0 &&
+               button_status.held_time >= button_status.oneshot {
+                button_status.pressed = false;
+                button_status.released_edge = true;
+                button_status.held_time = 0.0;
+                button_status.since_release = Some(0.0);
+                button_status.oneshot_expired = true;
+                if let Some(ref mut events) = self.events {
+                    events.push(InputAction::ButtonReleased(button.clone()));
+                }
+                if let Some(callbacks) = self.release_callbacks.get_mut(button) {
+                    for callback in callbacks.iter_mut() {
+                        (callback.0)();
+                    }
+                }
+            }
+            if button_status.buffered {
+                button_status.buffer_timer -= dt;
+                if button_status.buffer_timer < 0.0 {
+                    button_status.buffered = false;
+                }
+            }
+        }
+        self.any_input = false;
+        self.last_mouse_position = self.mouse_position;
+        self.mouse_scroll_delta = (0.0, 0.0);
+        // Scrolling has no release event, so it gets pressed for exactly
+        // one update; that gives buttons a pressed edge then a released
+        // one, and moves axes for one update's worth of acceleration.
+        let scrolls: Vec<InputEvent<Key>> = self.pending_scroll_releases.drain(..).collect();
+        for event in scrolls {
+            self.update_event(event, false);
+        }
+
+        self.time += dt;
+        if self.recording.is_some() {
+            self.recording_time += dt;
+        }
+        for seq in self.sequences.iter_mut() {
+            seq.triggered = false;
+        }
+        // Forget presses too old to continue any sequence.
+        let max_window = self.sequences.iter().fold(0.0, |acc, seq| f64::max(acc, seq.window));
+        if let Some(&(last_time, _)) = self.recent_presses.back() {
+            if self.time - last_time > max_window {
+                self.recent_presses.clear();
+            }
+        }
+
+        if self.replay.is_some() {
+            self.replay_time += dt;
+            self.feed_replay();
+        }
+    }
+
+    /// Sets how long each frame given to `update_frames()` is, in
+    /// seconds.  The default is 1/60.
+    pub fn set_fixed_timestep(&mut self, dt: f64) {
+        self.fixed_timestep = dt;
+    }
+
+    /// Advances by the given number of frames of the fixed timestep.
+    /// This is exactly the same as calling `update()` with the timestep
+    /// that many times, so edges only last for the first frame.
+    pub fn update_frames(&mut self, frames: u32) {
+        let dt = self.fixed_timestep;
+        for _ in 0..frames {
+            self.update(dt);
+        }
+    }
+
+    /// How many seconds of updates have gone by, adding up every `dt`
+    /// given to `update()` since the manager was made or `reset_time()`
+    /// was last called.  Handy for timestamping things against the
+    /// input.  It never goes down, and time spent paused doesn't count,
+    /// since `update()` ignores its `dt` then.
+    pub fn total_time(&self) -> f64 {
+        self.time
+    }
+
+    /// Sets `total_time()` back to zero.  Double taps and key sequences
+    /// are timed off the same clock, so any half-finished ones are
+    /// forgotten too.
+    pub fn reset_time(&mut self) {
+        self.time = 0.0;
+        self.recent_presses.clear();
+        for (_button, button_status) in self.buttons.iter_mut() {
+            button_status.last_press_time = None;
+        }
+    }
+
+    /// Returns everything that's happened to the axes and buttons since
+    /// the last call, oldest first, for feeding input into an event
+    /// driven game loop instead of polling.  Buttons show up when
+    /// they're pressed or released, and axes whenever `update()` finds
+    /// they've moved.  Nothing is collected until this is first called,
+    /// so managers that don't use it don't pile events up; call it once
+    /// when setting up to start.
+    pub fn drain_events(&mut self) -> Vec<InputAction<Axes, Buttons>> {
+        ::std::mem::replace(&mut self.events, Some(Vec::new())).unwrap_or_else(Vec::new)
+    }
+
+    /// Adds a function to call whenever the given button goes from
+    /// released to pressed.  Callbacks run right away, in the middle
+    /// of whichever `update_*()` method pressed the button, before
+    /// the button's state changes.  If there's more than one for a
+    /// button, they're called in the order they were added.
+    pub fn on_button_pressed(&mut self, button: Buttons, cb: Box<dyn FnMut()>) {
+        self.press_callbacks.entry(button).or_insert_with(Vec::new).push(ButtonCallback(cb));
+    }
+
+    /// Like `on_button_pressed()`, but for when the button
+    /// is released.
+    pub fn on_button_released(&mut self, button: Buttons, cb: Box<dyn FnMut()>) {
+        self.release_callbacks.entry(button).or_insert_with(Vec::new).push(ButtonCallback(cb));
+    }
+
+    /// Turns text input on or off, for things like name entry and
+    /// consoles.  While it's on, characters given to
+    /// `update_text_input()` are saved up for `take_text_input()`,
+    /// and key bindings are ignored unless turned back on with
+    /// `set_text_input_suppresses_keys()`.  Any bound keys held when
+    /// it's turned on are released, and turning it off throws away
+    /// any text that wasn't taken.
+    pub fn set_text_input_enabled(&mut self, enabled: bool) {
+        self.text_input_enabled = enabled;
+        if !enabled {
+            self.text_input.clear();
+        } else if self.text_input_suppresses_keys {
+            let held: Vec<Key> = self.held_keys.keys().cloned().collect();
+            for keycode in held {
+                self.release_key(&keycode);
+            }
+        }
+    }
+
+    pub fn text_input_enabled(&self) -> bool {
+        self.text_input_enabled
+    }
+
+    /// Sets whether key bindings are ignored while text input is on,
+    /// so typing a W doesn't also move the player.  Defaults to true.
+    pub fn set_text_input_suppresses_keys(&mut self, suppress: bool) {
+        self.text_input_suppresses_keys = suppress;
+    }
+
+    fn suppressing_keys(&self) -> bool {
+        self.text_input_enabled && self.text_input_suppresses_keys
+    }
+
+    /// This method should get called by your text_input_event handler,
+    /// for each character typed.  Does nothing unless text input is on.
+    pub fn update_text_input(&mut self, ch: char) {
+        if self.text_input_enabled {
+            self.text_input.push(ch);
+        }
+    }
+
+    /// Returns everything typed since the last call, and clears it.
+    pub fn take_text_input(&mut self) -> String {
+        ::std::mem::replace(&mut self.text_input, String::new())
+    }
+
+    /// Pauses or unpauses the input.  While paused, `update()` does
+    /// nothing: the axes don't move, button edges don't get cleared
+    /// and no time passes, so you can keep calling it as usual.
+    /// Input events are still taken, so what's held down when the
+    /// game resumes is right, but a press during the pause keeps its
+    /// pressed edge until the first update after resuming.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Turns off all tweening, for every axis at once: while on, axes
+    /// jump straight to where they're pushed and straight back when
+    /// let go of, as if they all had snap set.  That makes
+    /// `get_axis()` report the same as `get_axis_raw()`, unless the
+    /// axis is inverted, scaled or has a deadzone, so it's good for
+    /// games that need frame-perfect input.  Analog input isn't
+    /// affected, and axes with hold set (including stepped ones) still
+    /// stay where they are when let go of.
+    pub fn set_instant_mode(&mut self, instant: bool) {
+        self.instant_mode = instant;
+    }
+
+    /// Starts listening for a key to bind to the given effect.
+    /// The next key pressed gets bound to it instead of doing
+    /// whatever it would normally do.
+    pub fn begin_rebind(&mut self, effect: BoundEffect<Axes, Buttons>) {
+        self.pending_rebind = Some(effect.into());
+        self.displaced_bindings.clear();
+    }
+
+    /// Returns true if we're still waiting for a key from
+    /// `begin_rebind()`.
+    pub fn rebind_pending(&self) -> bool {
+        self.pending_rebind.is_some()
+    }
+
+    /// Stops waiting for a key from `begin_rebind()`.
+    pub fn cancel_rebind(&mut self) {
+        self.pending_rebind = None;
+    }
+
+    /// Once a rebind has finished, returns whatever else the
+    /// captured key used to be bound to, which is now replaced.
+    /// Useful for warning the player about conflicts.
+    pub fn take_displaced_bindings(&mut self) -> Vec<BoundEffect<Axes, Buttons>> {
+        ::std::mem::replace(&mut self.displaced_bindings, Vec::new())
+    }
+
+    /// Makes rebinding swap keys when the captured key is already
+    /// in use: if the player gives action A a key that action B had,
+    /// B takes over A's old key instead of being left unbound.  A
+    /// also stops being bound to its old key, so the two really do
+    /// trade places.
+    ///
+    /// Only like swaps with like.  A button can't sensibly drive an
+    /// axis slot or vice versa (an axis key usually comes in a pair
+    /// with its opposite direction), so a button that conflicts with
+    /// an axis, or an axis with a button, is just displaced as usual
+    /// and shows up in `take_displaced_bindings()`.  The same goes if
+    /// A had no key to give away.  Off by default.
+    pub fn set_swap_bindings_on_conflict(&mut self, swap: bool) {
+        self.swap_on_conflict = swap;
+    }
+
+    /// Once a rebind has finished, returns the actions that were
+    /// swapped onto the rebound effect's old key, along with the key
+    /// each one got.  Always empty unless
+    /// `set_swap_bindings_on_conflict()` is on.
+    pub fn take_swapped_bindings(&mut self) -> Vec<(BoundEffect<Axes, Buttons>, Key)> {
+        ::std::mem::replace(&mut self.swapped_bindings, Vec::new())
+    }
+
+    /// Starts logging every key press and release given to
+    /// `update_keydown()` and `update_keyup()`, along with when it
+    /// happened.  Starting again while already recording throws
+    /// away what was recorded so far.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+        self.recording_time = 0.0;
+    }
+
+    /// Stops recording and returns everything that was recorded,
+    /// oldest first.  Returns an empty Vec if nothing was being
+    /// recorded.
+    pub fn take_recording(&mut self) -> Vec<InputFrame<Key>> {
+        self.recording.take().unwrap_or_default()
+    }
+
+    fn record(&mut self, event: InputEvent<Key>, down: bool, repeat: bool) {
+        let time = self.recording_time;
+        if let Some(ref mut frames) = self.recording {
+            frames.push(InputFrame {
+                time: time,
+                down: down,
+                repeat: repeat,
+                event: event,
+            });
+        }
+    }
+
+    /// Plays back the given recording from `take_recording()`.
+    /// Each recorded key event happens at the end of the `update()`
+    /// call that brings the elapsed time up to when it was recorded,
+    /// just as if it came in between that call and the next; until
+    /// the last one has happened, live key input is ignored.
+    /// Any keys held when this is called are released.
+    ///
+    /// Given the same sequence of `dt`'s the recording was made with,
+    /// this reproduces exactly the same axis and button states.  The
+    /// elapsed time is added up the same way as when recording, so
+    /// the times compare equal despite being f64's; with different
+    /// `dt`'s, events land on the first update at or after their
+    /// time, and the tweening will differ slightly.
+    pub fn play_recording(&mut self, frames: Vec<InputFrame<Key>>) {
+        let held: Vec<Key> = self.held_keys.keys().cloned().collect();
+        for keycode in held {
+            self.release_key(&keycode);
+        }
+        self.replay = Some(frames.into_iter().collect());
+        self.replay_time = 0.0;
+        self.feed_replay();
+    }
+
+    /// Returns true once everything in the recording given to
+    /// `play_recording()` has been played back, and live input
+    /// is being used again.  Also true if nothing was
+    /// ever played back.
+    pub fn replay_finished(&self) -> bool {
+        self.replay.is_none()
+    }
+
+    /// Does all the replayed events that are due by now.
+    fn feed_replay(&mut self) {
+        let now = self.replay_time;
+        let due: Vec<InputFrame<Key>> = match self.replay {
+            Some(ref mut frames) => {
+                let count = frames.iter().take_while(|frame| frame.time <= now).count();
+                frames.drain(..count).collect()
+            }
+            None => return,
+        };
+        for frame in due {
+            if let InputEvent::KeyEvent(keycode, keymod) = frame.event {
+                if frame.down {
+                    self.key_down(keycode, keymod, frame.repeat);
+                } else {
+                    self.key_up(keycode);
+                }
+            }
+        }
+        if self.replay.as_ref().map_or(false, |frames| frames.is_empty()) {
+            self.replay = None;
+        }
+    }
+
+    /// Sets whether key repeats, from holding a key down, get ignored.
+    /// If not, each repeat presses the key's buttons again, giving them
+    /// a new pressed edge and buffered press; handy for scrolling
+    /// through menus.  Defaults to true.
+    pub fn set_ignore_repeats(&mut self, ignore: bool) {
+        self.ignore_repeats = ignore;
+    }
+
+    /// This method should get called by your key_down_event handler,
+    /// along with whether it's a key repeat.
+    /// See `bind_key_mod_to_button()` for how the modifiers are used.
+    ///
+    /// Returns true if the key is bound to something (counting chords),
+    /// so if you have several input managers layered on top of each
+    /// other you can stop passing on keys once one of them is used.
+    /// Disabled keys, reserved combos, ignored repeats and the like
+    /// return false.
+    pub fn update_keydown(&mut self, keycode: Option<Key>, keymod: Mod, repeat: bool) -> bool {
+        if let Some(keycode) = keycode {
+            self.pressed_keys.insert(keycode.clone());
+            if self.replay.is_some() || (repeat && self.ignore_repeats) {
+                return false;
+            }
+            self.record(InputEvent::KeyEvent(keycode.clone(), keymod), true, repeat);
+            self.key_down(keycode, keymod, repeat)
+        } else {
+            false
+        }
+    }
+
+    /// This method should get called by your key_up_event handler.
+    /// Returns true if the key is bound to something, like
+    /// `update_keydown()`.  A release whose press was ignored (and
+    /// returned false) is ignored too, and returns false.
+    pub fn update_keyup(&mut self, keycode: Option<Key>, keymod: Mod) -> bool {
+        if let Some(keycode) = keycode {
+            self.pressed_keys.remove(&keycode);
+            if self.replay.is_some() {
+                return false;
+            }
+            self.record(InputEvent::KeyEvent(keycode.clone(), keymod), false, false);
+            self.key_up(keycode)
+        } else {
+            false
+        }
+    }
+
+    /// Whether the given key, which triggered the given input,
+    /// does anything.
+    fn key_handled(&self, keycode: &Key, event: &InputEvent<Key>) -> bool {
+        self.lookup_effects(event).is_some() ||
+        self.chords.iter().any(|chord| chord.keys.contains(keycode)) ||
+        self.stepped_keys.iter().any(|&(ref k, _, _)| k == keycode)
+    }
+
+    fn key_down(&mut self, keycode: Key, keymod: Mod, repeat: bool) -> bool {
+        if self.is_reserved(&keycode, keymod) {
+            return false;
+        }
+        if let Some(effect) = self.pending_rebind.take() {
+            let event = InputEvent::KeyEvent(keycode.clone(), Mod::empty());
+            let new = BoundEffect::from(effect.clone());
+            let displaced: Vec<_> = self.bindings_for_key(keycode.clone())
+                .into_iter()
+                .filter(|old| *old != new)
+                .collect();
+            let same_kind = |old: &BoundEffect<Axes, Buttons>| match (old, &new) {
+                (&BoundEffect::Axis(..), &BoundEffect::Axis(..)) |
+                (&BoundEffect::Button(_), &BoundEffect::Button(_)) => true,
+                _ => false,
+            };
+            let old_key = if self.swap_on_conflict && displaced.iter().any(&same_kind) {
+                self.unbind_effect_from_keys(&effect)
+                    .into_iter()
+                    .find(|k| *k != keycode)
+            } else {
+                None
+            };
+            self.unbind_key(keycode);
+            self.add_binding(event, effect);
+            self.displaced_bindings.clear();
+            self.swapped_bindings.clear();
+            for old in displaced {
+                match old_key {
+                    Some(ref old_key) if same_kind(&old) => {
+                        let old_event = InputEvent::KeyEvent(old_key.clone(), Mod::empty());
+                        self.add_binding(old_event, old.clone().into());
+                        self.swapped_bindings.push((old, old_key.clone()));
+                    }
+                    _ => self.displaced_bindings.push(old),
+                }
+            }
+            return true;
+        }
+        if !self.key_enabled(&keycode) || self.suppressing_keys() {
+            return false;
+        }
+        // If the key is already down (ie, this is a key repeat),
+        // keep using whatever binding it triggered the first time.
+        let event = match self.held_keys.get(&keycode).cloned() {
+            Some(event) => {
+                if repeat {
+                    let handled = self.key_handled(&keycode, &event);
+                    self.repeat_event(event);
+                    return handled;
+                }
+                event
+            }
+            None => {
+                self.update_sequences(&keycode);
+                self.step_axes(&keycode);
+                self.resolve_key(&keycode, keymod)
+            }
+        };
+        let handled = self.key_handled(&keycode, &event);
+        self.held_keys.insert(keycode, event.clone());
+        self.update_event(event, true);
+        self.update_chords();
+        handled
+    }
+
+    /// Gives the buttons of an input that's already held a new
+    /// press, for key repeats.
+    fn repeat_event(&mut self, event: InputEvent<Key>) {
+        let effects = match self.lookup_effects(&event) {
+            Some(e) => e.clone(),
+            None => return,
+        };
+        for effect in effects {
+            if let InputEffect::Button(button) = effect {
+                if let Some(button_status) = self.buttons.get_mut(&button) {
+                    if button_status.pressed {
+                        button_status.pressed_edge = true;
+                        button_status.buffered = true;
+                        button_status.buffer_timer = button_status.buffer;
+                    }
+                }
+            }
+        }
+    }
+
+    fn key_up(&mut self, keycode: Key) -> bool {
+        if !self.key_enabled(&keycode) {
+            return false;
+        }
+        // If the press never got through (suppressed by text input,
+        // a reserved combo and so on), neither does the release, so
+        // it goes on to whatever got the press instead.
+        let event = match self.held_keys.remove(&keycode) {
+            Some(event) => event,
+            None => return false,
+        };
+        let handled = self.key_handled(&keycode, &event);
+        self.update_event(event, false);
+        self.update_chords();
+        handled
+    }
+
+    /// Records a key press and checks whether it finished
+    /// any sequences.
+    fn update_sequences(&mut self, keycode: &Key) {
+        if self.sequences.is_empty() {
+            return;
+        }
+        let max_len = self.sequences.iter().map(|seq| seq.steps.len()).max().unwrap_or(0);
+        self.recent_presses.push_back((self.time, keycode.clone()));
+        while self.recent_presses.len() > max_len {
+            self.recent_presses.pop_front();
+        }
+
+        let mut any_triggered = false;
+        for seq in self.sequences.iter_mut() {
+            let presses = &self.recent_presses;
+            if presses.len() < seq.steps.len() {
+                continue;
+            }
+            let start = presses.len() - seq.steps.len();
+            let mut matched = true;
+            for (i, step) in seq.steps.iter().enumerate() {
+                let (time, ref key) = presses[start + i];
+                let in_time = i == 0 || time - presses[start + i - 1].0 <= seq.window;
+                if key != step || !in_time {
+                    matched = false;
+                    break;
+                }
+            }
+            if matched {
+                seq.triggered = true;
+                any_triggered = true;
+            }
+        }
+        // Don't let the same presses trigger things twice.
+        if any_triggered {
+            self.recent_presses.clear();
+        }
+    }
+
+    /// Presses or releases chord buttons whenever the set
+    /// of held keys changes.
+    fn update_chords(&mut self) {
+        let mut changed = Vec::new();
+        for (i, chord) in self.chords.iter_mut().enumerate() {
+            let held = &self.held_keys;
+            let all_held = chord.keys.iter().all(|k| held.contains_key(k));
+            if all_held != chord.active {
+                chord.active = all_held;
+                changed.push((i, chord.button.clone(), all_held));
+            }
+        }
+        for (i, button, started) in changed {
+            self.update_effect(InputEvent::ChordEvent(i), InputEffect::Button(button), started);
+        }
+    }
+
+    /// Finds the most specific key binding matching the given key
+    /// and held modifiers, in the topmost visible context that has
+    /// any.  If two bindings have the same number of modifiers, the
+    /// one with the larger bitmask wins, just so it's consistent.
+    fn resolve_key(&self, keycode: &Key, keymod: Mod) -> InputEvent<Key> {
+        let unmodified = InputEvent::KeyEvent(keycode.clone(), Mod::empty());
+        for layer in self.visible_bindings() {
+            let mut best = None;
+            let mut best_mods = Mod::empty();
+            for event in layer.keys() {
+                if let InputEvent::KeyEvent(ref k, mods) = *event {
+                    if k != keycode || !keymod.contains(mods) {
+                        continue;
+                    }
+                    let count = mods.bits().count_ones();
+                    let best_count = best_mods.bits().count_ones();
+                    if best.is_none() || count > best_count ||
+                       (count == best_count && mods.bits() > best_mods.bits()) {
+                        best = Some(event.clone());
+                        best_mods = mods;
+                    }
+                }
+            }
+            if let Some(event) = best {
+                return event;
+            }
+        }
+        unmodified
+    }
+
+    /// This method should get called by your mouse_button_down_event handler.
+    pub fn update_mousedown(&mut self, button: MouseButton) {
+        self.update_event(InputEvent::MouseButtonEvent(button), true);
+        let position = self.mouse_position;
+        for drag in self.mouse_drags.iter_mut() {
+            if drag.button == button {
+                drag.origin = Some(position);
+            }
+        }
+        self.update_mouse_drags(None);
+    }
+
+    /// This method should get called by your mouse_button_up_event handler.
+    pub fn update_mouseup(&mut self, button: MouseButton) {
+        self.update_event(InputEvent::MouseButtonEvent(button), false);
+        for drag in self.mouse_drags.iter_mut() {
+            if drag.button == button {
+                drag.origin = None;
+            }
+        }
+        self.update_mouse_drags(Some(button));
+    }
+
+    /// Sets the axes of the mouse drags in progress to match where the
+    /// mouse is now, and those of `released` back to 0.
+    fn update_mouse_drags(&mut self, released: Option<MouseButton>) {
+        let (x, y) = self.mouse_position;
+        let mut values = Vec::new();
+        for drag in &self.mouse_drags {
+            if drag.origin.is_none() && Some(drag.button) != released {
+                continue;
+            }
+            let (dx, dy) = match drag.origin {
+                Some((origin_x, origin_y)) => {
+                    ((x - origin_x) as f64 / drag.pixels_per_unit,
+                     (y - origin_y) as f64 / drag.pixels_per_unit)
+                }
+                None => (0.0, 0.0),
+            };
+            values.push((drag.x_axis.clone(), dx));
+            values.push((drag.y_axis.clone(), dy));
+        }
+        for (axis, value) in values {
+            self.update_axis_analog(axis, value);
+        }
+    }
+
+    /// This method should get called by your mouse_motion_event handler,
+    /// with how far the mouse moved.
+    pub fn update_mouse_motion(&mut self, dx: i32, dy: i32) {
+        for &(mouse_axis, delta) in &[(MouseAxis::X, dx), (MouseAxis::Y, dy)] {
+            if delta == 0 {
+                continue;
+            }
+            let event = InputEvent::MouseMotionEvent(mouse_axis);
+            let effects = match self.lookup_effects(&event) {
+                Some(e) => e.clone(),
+                None => continue,
+            };
+            for effect in effects {
+                if let InputEffect::Axis(axis, value) = effect {
+                    let sensitivity = self.mouse_sensitivities
+                        .get(&(mouse_axis, axis.clone()))
+                        .cloned()
+                        .unwrap_or(0.0);
+                    let amount = delta as f64 * sensitivity;
+                    let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+                    let position = axis_status.position + amount * value;
+                    let position = axis_status.clamped(position);
+                    axis_status.set_position(position);
+                    axis_status.analog = false;
+                }
+            }
+        }
+    }
+
+    /// This method should get called by your mouse_wheel_event handler.
+    /// Positive y is scrolling up, and positive x is to the right.
+    pub fn update_mouse_scroll(&mut self, x: f32, y: f32) {
+        self.mouse_scroll_delta.0 += x;
+        self.mouse_scroll_delta.1 += y;
+        self.scroll_remainder += y;
+        let mut directions = Vec::new();
+        if y > 0.0 {
+            directions.push(ScrollDirection::Up);
+        } else if y < 0.0 {
+            directions.push(ScrollDirection::Down);
+        }
+        if x > 0.0 {
+            directions.push(ScrollDirection::Right);
+        } else if x < 0.0 {
+            directions.push(ScrollDirection::Left);
+        }
+        for direction in directions {
+            let event = InputEvent::MouseScrollEvent(direction);
+            self.update_event(event.clone(), true);
+            self.pending_scroll_releases.push(event);
+        }
+    }
+
+    /// Returns how many whole notches the wheel has been scrolled
+    /// vertically since the last call, positive for up, such as for
+    /// stepping through an inventory.  Whatever's left over that
+    /// doesn't make a whole notch is kept for next time, so the many
+    /// tiny scrolls trackpads send still add up.
+    pub fn scroll_notches(&mut self) -> i32 {
+        let notches = (self.scroll_remainder / self.scroll_notch_size).trunc();
+        self.scroll_remainder -= notches * self.scroll_notch_size;
+        notches as i32
+    }
+
+    /// Sets how much scrolling counts as one notch for
+    /// `scroll_notches()`.  Defaults to 1.0.  Sizes that aren't more
+    /// than 0 are ignored.
+    pub fn set_scroll_notch_size(&mut self, step: f32) {
+        if step > 0.0 {
+            self.scroll_notch_size = step;
+        }
+    }
+
+    /// This method should get called by your controller_button_down_event
+    /// handler, with the id of the controller it came from.
+    pub fn update_controller_button_down(&mut self, controller: usize, btn: Button) {
+        self.update_event(InputEvent::ControllerButtonEvent(controller, btn), true);
+    }
+
+    /// This method should get called by your controller_button_up_event
+    /// handler, with the id of the controller it came from.
+    pub fn update_controller_button_up(&mut self, controller: usize, btn: Button) {
+        self.update_event(InputEvent::ControllerButtonEvent(controller, btn), false);
+    }
+
+    /// This method should get called whenever a controller hat moves,
+    /// with the id of the hat and where it's pushed now.  Each way the
+    /// hat stops or starts pushing gets pressed or released like a key.
+    pub fn update_controller_hat(&mut self, hat: usize, position: HatPosition) {
+        let old = self.hat_positions.insert(hat, position).unwrap_or(HatPosition::Centered);
+        let directions = [HatPosition::Up,
+                          HatPosition::Down,
+                          HatPosition::Left,
+                          HatPosition::Right];
+        for &direction in directions.iter() {
+            let held = position.pushes(direction);
+            if held != old.pushes(direction) {
+                self.update_event(InputEvent::ControllerHatEvent(hat, direction), held);
+            }
+        }
+    }
+
+    /// This method should get called by your controller_axis_event
+    /// handler, with the id of the controller it came from.
+    pub fn update_controller_axis(&mut self, controller: usize, controller_axis: Axis, value: i16) {
+        let event = InputEvent::ControllerAxisEvent(controller, controller_axis);
+        let effects = {
+            if let Some(e) = self.lookup_effects(&event) {
+                e.clone()
+            } else {
+                return;
+            }
+        };
+        let value = f64::max(-1.0, value as f64 / i16::MAX as f64);
+        for effect in effects {
+            if let InputEffect::Axis(axis, scale) = effect {
+                self.update_axis_analog(axis, value * scale);
+            }
+        }
+    }
+
+    /// Sets the position of the given axis directly, for analog
+    /// inputs.  The deadzone still applies, but the acceleration and
+    /// gravity don't; the axis stays where it is put until either
+    /// this is called again or a key bound to the axis is pressed.
+    /// See `set_axis_blend()` for other ways of mixing analog and
+    /// key inputs.
+    pub fn update_axis_analog(&mut self, axis: Axes, value: f64) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        let value = f64::max(axis_status.min, f64::min(axis_status.max, value));
+        let position = if f64::abs(value) < axis_status.deadzone {
+            0.0
+        } else {
+            value
+        };
+        axis_status.analog_value = position;
+        axis_status.analog = true;
+        if axis_status.blend == AxisBlend::LastWins {
+            axis_status.set_position(position);
+        } else {
+            axis_status.combine_sources();
+        }
+    }
+
+    /// Presses or releases the given button directly, as if a key
+    /// bound to it went down or up, without needing any binding.
+    /// Meant for scripted tutorials and demo play.  Everything past
+    /// the bindings treats injected input the same as the real
+    /// thing: edges, callbacks, toggles and so on all work as usual,
+    /// and the button counts as held while either a real input or an
+    /// injected press holds it.
+    pub fn inject_button(&mut self, button: Buttons, down: bool) {
+        self.update_effect(InputEvent::InjectedEvent, InputEffect::Button(button), down);
+    }
+
+    /// Pushes the given axis directly, as if a key bound to it were
+    /// held: 1 pushes it positive, -1 negative, and 0 lets go.  Like
+    /// `inject_button()`, this skips the bindings but is otherwise
+    /// just like real input, so the axis gets there at its usual
+    /// acceleration.
+    pub fn inject_axis(&mut self, axis: Axes, direction: i8) {
+        let event = InputEvent::InjectedEvent;
+        let positive = InputEffect::Axis(axis.clone(), 1.0);
+        let negative = InputEffect::Axis(axis, -1.0);
+        self.update_effect(event.clone(), positive, direction > 0);
+        self.update_effect(event, negative, direction < 0);
+    }
+
+    /// Applies all the effects bound to the given input.
+    fn update_event(&mut self, event: InputEvent<Key>, started: bool) {
+        let effects = {
+            if let Some(e) = self.lookup_effects(&event) {
+                e.clone()
+            } else {
+                return;
+            }
+        };
+        for effect in effects {
+            self.update_effect(event.clone(), effect, started);
+        }
+    }
+
+    /// This method should get called by your mouse_motion_event handler.
+    pub fn update_mouse_position(&mut self, x: i32, y: i32) {
+        self.mouse_position = (x, y);
+        self.update_mouse_drags(None);
+    }
+
+    /// Takes an InputEffect and actually applies it.
+    fn update_effect(&mut self,
+                     event: InputEvent<Key>,
+                     effect: InputEffect<Axes, Buttons>,
+                     started: bool) {
+        if started {
+            self.any_input = true;
+        }
+        match effect {
+            InputEffect::Axis(axis, value) => {
+                let f = || AxisStatus::default();
+                let axis_status = self.axes.entry(axis).or_insert_with(f);
+                {
+                    let inputs = if value >= 0.0 {
+                        &mut axis_status.positive_inputs
+                    } else {
+                        &mut axis_status.negative_inputs
+                    };
+                    if started {
+                        inputs.insert(event, f64::abs(value));
+                    } else {
+                        inputs.remove(&event);
+                    }
+                }
+                if started {
+                    axis_status.analog = false;
+                }
+                axis_status.update_direction();
+                let held = axis_status.hold && axis_status.direction == 0.0;
+                if axis_status.snap || (self.instant_mode && !held) {
+                    let target = axis_status.snap_target();
+                    axis_status.set_position(target);
+                }
+            }
+            InputEffect::Button(button) => {
+                let time = self.time;
+                let f = || ButtonStatus::default();
+                let button_status = self.buttons.entry(button.clone()).or_insert_with(f);
+                let was_held = !button_status.held_by.is_empty();
+                if started {
+                    button_status.held_by.insert(event);
+                } else {
+                    button_status.held_by.remove(&event);
+                }
+                let held = !button_status.held_by.is_empty();
+                if !held {
+                    button_status.oneshot_expired = false;
+                }
+                let pressed = if button_status.toggle {
+                    button_status.pressed != (held && !was_held)
+                } else {
+                    held && !button_status.oneshot_expired
+                };
+                let callbacks = if pressed && !button_status.pressed {
+                    self.press_callbacks.get_mut(&button)
+                } else if !pressed && button_status.pressed {
+                    self.release_callbacks.get_mut(&button)
+                } else {
+                    None
+                };
+                for callback in callbacks.into_iter().flat_map(|c| c.iter_mut()) {
+                    (callback.0)();
+                }
+                if let Some(ref mut events) = self.events {
+                    if pressed && !button_status.pressed {
+                        events.push(InputAction::ButtonPressed(button.clone()));
+                    } else if !pressed && button_status.pressed {
+                        events.push(InputAction::ButtonReleased(button.clone()));
+                    }
+                }
+                if pressed && !button_status.pressed {
+                    button_status.pressed_edge = true;
+                    button_status.buffered = true;
+                    button_status.buffer_timer = button_status.buffer;
+                    button_status.held_time = 0.0;
+                    button_status.since_press = Some(0.0);
+                    if button_status.double_tap_window > 0.0 {
+                        match button_status.last_press_time {
+                            Some(t) if time - t <= button_status.double_tap_window => {
+                                button_status.double_tapped = true;
+                                button_status.last_press_time = None;
+                            }
+                            _ => button_status.last_press_time = Some(time),
+                        }
+                    }
+                } else if !pressed && button_status.pressed {
+                    button_status.released_edge = true;
+                    button_status.held_time = 0.0;
+                    button_status.since_release = Some(0.0);
+                }
+                button_status.pressed = pressed;
+            }
+        }
+    }
+
+    /// Returns the current value of the axis, or 0 if nothing's
+    /// ever been bound to it.
+    pub fn get_axis(&self, axis: Axes) -> f64 {
+        let value = self.axes.get(&axis).map_or(0.0, |axis_status| {
+            axis_status.queried.set(true);
+            axis_status.output()
+        });
+        match self.global_axis_scale {
+            Some((ref keycode, scale)) if self.pressed_keys.contains(keycode) => value * scale,
+            _ => value,
+        }
+    }
+
+    /// Makes holding the given key scale what `get_axis()` reports for
+    /// every axis, such as for a walk key that slows all movement.  The
+    /// key works on its own, apart from any bindings, and the axes move
+    /// the same as ever; only what they report changes.  There's only
+    /// one such key, so setting another replaces it.
+    pub fn set_global_axis_scale_key(&mut self, keycode: Key, scale: f64) {
+        self.global_axis_scale = Some((keycode, scale));
+    }
+
+    /// Returns which way the axis is being pushed, without any
+    /// tweening: -1, 0 or +1, or in between for keys bound with
+    /// a magnitude.  See also `get_direction()`.
+    pub fn get_axis_raw(&self, axis: Axes) -> f64 {
+        self.axes.get(&axis).map_or(0.0, |axis_status| {
+            axis_status.queried.set(true);
+            axis_status.direction
+        })
+    }
+
+    /// Returns which way the axis is being pushed as -1, 0 or 1, for
+    /// things like grid movement that don't want any tweening.  Keys
+    /// that push it only part way still count as 1.
+    pub fn get_direction(&self, axis: Axes) -> i8 {
+        let direction = self.get_axis_raw(axis);
+        if direction > 0.0 {
+            1
+        } else if direction < 0.0 {
+            -1
+        } else {
+            0
+        }
+    }
+
+    /// Returns -1 or 1 for which way the given axis was last pushed,
+    /// even once it's been let go of, such as for which way a
+    /// character faces while standing still.  Returns 0 if it's
+    /// never been pushed.
+    pub fn last_nonzero_direction(&self, axis: Axes) -> f64 {
+        self.axes.get(&axis).map_or(0.0, |axis_status| axis_status.last_direction)
+    }
+
+    /// Returns true if nothing is pushing the given axis and it has
+    /// settled back to where it rests, give or take the tolerance from
+    /// `set_axis_rest_tolerance()`.  Axes that were never used are
+    /// always at rest.
+    pub fn axis_at_rest(&self, axis: Axes) -> bool {
+        self.axes.get(&axis).map_or(true, |axis_status| {
+            axis_status.direction == 0.0 &&
+            f64::abs(axis_status.position - axis_status.rest()) < axis_status.rest_tolerance
+        })
+    }
+
+    /// Returns how fast the axis moved over the last `update()`, in
+    /// units per second, counting any jumps from snapping or analog
+    /// input since the update before that.
+    pub fn get_axis_velocity(&self, axis: Axes) -> f64 {
+        self.axes.get(&axis).map_or(0.0, |axis_status| axis_status.velocity)
+    }
+
+    /// Makes `update()` remember the given axis' position for the last
+    /// `samples` updates, for smoothing or extrapolating it.  0 turns
+    /// this off again.  Any positions already remembered are dropped.
+    pub fn enable_axis_history(&mut self, axis: Axes, samples: usize) {
+        let axis_status = self.axes.entry(axis).or_insert_with(AxisStatus::default);
+        axis_status.history_samples = samples;
+        axis_status.history = Vec::with_capacity(samples);
+    }
+
+    /// Returns the positions remembered for the given axis, oldest
+    /// first.  Empty unless `enable_axis_history()` was called for it.
+    pub fn axis_history(&self, axis: Axes) -> &[f64] {
+        self.axes.get(&axis).map_or(&[], |axis_status| &axis_status.history)
+    }
+
+    /// Returns how many updates the given axis has finished at one
+    /// end of its range, since it was made or since the last call to
+    /// `reset_saturation_counters()`.  When tuning sensitivities and
+    /// deadzones, an analog axis that's often here is being overdriven.
+    pub fn axis_saturation_count(&self, axis: Axes) -> u32 {
+        self.axes.get(&axis).map_or(0, |axis_status| axis_status.saturation_count)
+    }
+
+    /// Sets the counts from `axis_saturation_count()` back to 0.
+    pub fn reset_saturation_counters(&mut self) {
+        for axis_status in self.axes.values_mut() {
+            axis_status.saturation_count = 0;
+        }
+    }
+
+    /// Returns the values of two axes as an (x, y) vector,
+    /// handy for top-down movement.
+    pub fn get_axes_vector(&self, x_axis: Axes, y_axis: Axes) -> (f64, f64) {
+        (self.get_axis(x_axis), self.get_axis(y_axis))
+    }
+
+    /// Like `get_axes_vector()`, but if the vector is longer than 1
+    /// it gets scaled down to length 1, so that moving diagonally
+    /// isn't faster than moving straight.
+    pub fn get_axes_vector_normalized(&self, x_axis: Axes, y_axis: Axes) -> (f64, f64) {
+        let (x, y) = self.get_axes_vector(x_axis, y_axis);
+        clamp_vector_magnitude(x, y, 1.0)
+    }
+
+    /// Like `get_axes_vector()`, but turns the vector to the nearest of
+    /// the given number of evenly spaced directions, starting from
+    /// straight along the x axis.  4 gives you just the axis
+    /// directions and 8 adds the diagonals.  The length stays the same,
+    /// so an analog stick pushed halfway still only goes halfway.  A
+    /// zero vector stays zero, and 0 directions leaves the vector
+    /// alone.
+    pub fn get_axes_vector_snapped(&self, x_axis: Axes, y_axis: Axes, directions: u8)
+                                   -> (f64, f64) {
+        let (x, y) = self.get_axes_vector(x_axis, y_axis);
+        let length = f64::sqrt(x * x + y * y);
+        if length == 0.0 || directions == 0 {
+            return (x, y);
+        }
+        let step = 2.0 * ::std::f64::consts::PI / directions as f64;
+        let angle = (f64::atan2(y, x) / step).round() * step;
+        (length * angle.cos(), length * angle.sin())
+    }
+
+    /// Returns the two axes as an (x, y) stick position, with the
+    /// radial deadzone from `set_stick_deadzone()` applied.  Past the
+    /// deadzone the length is scaled back up so it still goes smoothly
+    /// from 0 to 1, and it's never longer than 1.
+    pub fn get_stick(&self, x_axis: Axes, y_axis: Axes) -> (f64, f64) {
+        let deadzone = self.sticks
+            .iter()
+            .find(|pair| pair.x_axis == x_axis && pair.y_axis == y_axis)
+            .map_or(0.0, |pair| pair.deadzone);
+        let (x, y) = self.get_axes_vector(x_axis, y_axis);
+        let length = f64::sqrt(x * x + y * y);
+        if length == 0.0 || length <= deadzone {
+            return (0.0, 0.0);
+        }
+        let scaled = f64::min(1.0, (length - deadzone) / (1.0 - deadzone));
+        (x * scaled / length, y * scaled / length)
+    }
+
+    /// Returns an iterator over every axis the manager knows of, with
+    /// its raw position and the direction it's being pushed in, in no
+    /// particular order.  Handy for debug overlays.
+    pub fn axes_iter<'a>(&'a self) -> impl Iterator<Item = (Axes, f64, f64)> + 'a {
+        self.axes
+            .iter()
+            .map(|(axis, status)| (axis.clone(), status.position, status.direction))
+    }
+
+    /// Returns true if the given button is held down by any of the
+    /// inputs bound to it.
+    pub fn get_button(&self, axis: Buttons) -> bool {
+        if let Some(button_status) = self.buttons.get(&axis) {
+            button_status.queried.set(true);
+            button_status.pressed
+        } else {
+            false
+        }
+    }
+
+    /// Returns whether each of the given buttons is held down, packed
+    /// into one number: bit 0 (the lowest) for the first button, bit 1
+    /// for the second and so on, as with `get_button()`.  Only the
+    /// first 64 buttons are included.
+    pub fn button_mask(&self, buttons: &[Buttons]) -> u64 {
+        buttons.iter()
+            .take(64)
+            .enumerate()
+            .filter(|&(_, button)| self.get_button(button.clone()))
+            .fold(0, |mask, (i, _)| mask | 1 << i)
+    }
+
+    /// Sets how long a press of the given button is remembered
+    /// by `consume_button_press()`, in seconds.  Defaults to 0, which
+    /// remembers it until the next `update()`.
+    pub fn set_button_buffer(&mut self, button: Buttons, seconds: f64) {
+        let button_status = self.buttons.entry(button).or_insert_with(ButtonStatus::default);
+        button_status.buffer = seconds;
+    }
+
+    /// Returns true if the button was pressed within its buffer time
+    /// (see `set_button_buffer()`) and that press hasn't been consumed
+    /// yet, then forgets the press.  Good for things like letting a
+    /// jump pressed just before landing still count.
+    pub fn consume_button_press(&mut self, button: Buttons) -> bool {
+        if let Some(button_status) = self.buttons.get_mut(&button) {
+            let buffered = button_status.buffered;
+            button_status.buffered = false;
+            buffered
+        } else {
+            false
+        }
+    }
+
+    /// Returns how long the given button has been held down, in
+    /// seconds, as counted by `update()`.  0 if it isn't pressed.
+    pub fn button_held_duration(&self, button: Buttons) -> f64 {
+        if let Some(button_status) = self.buttons.get(&button) {
+            button_status.held_time
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns how long ago the given button was last pressed, in
+    /// seconds as counted by `update()`, whether or not it's still
+    /// held.  None if it's never been pressed.
+    pub fn time_since_press(&self, button: Buttons) -> Option<f64> {
+        self.buttons.get(&button).and_then(|button_status| button_status.since_press)
+    }
+
+    /// Like `time_since_press()`, but for when it was last released.
+    pub fn time_since_release(&self, button: Buttons) -> Option<f64> {
+        self.buttons.get(&button).and_then(|button_status| button_status.since_release)
+    }
+
+    /// Makes holding the given button for `duration` seconds count as
+    /// a long press, such as for "hold to confirm" prompts.
+    pub fn set_button_long_press(&mut self, button: Buttons, duration: f64) {
+        let button_status = self.buttons.entry(button).or_insert_with(ButtonStatus::default);
+        button_status.long_press = duration;
+    }
+
+    /// Returns true once the given button has been held down for its
+    /// long press duration, and for as long as it stays down after.
+    pub fn get_long_press(&self, button: Buttons) -> bool {
+        self.long_press_progress(button) >= 1.0
+    }
+
+    /// Returns how far along its long press the given button is, from
+    /// 0 to 1.  Releasing the button puts this back to 0.  Always 0 if
+    /// the button has no long press duration.
+    pub fn long_press_progress(&self, button: Buttons) -> f64 {
+        if let Some(button_status) = self.buttons.get(&button) {
+            if button_status.long_press > 0.0 && button_status.pressed {
+                return f64::min(button_status.held_time / button_status.long_press, 1.0);
+            }
+        }
+        0.0
+    }
+
+    /// Makes the given button a toggle, like for crouching or switching
+    /// between walking and running: each press flips it between down
+    /// and up, with the usual edges, and releases are ignored.
+    pub fn set_button_toggle(&mut self, button: Buttons, toggle: bool) {
+        let button_status = self.buttons.entry(button).or_insert_with(ButtonStatus::default);
+        button_status.toggle = toggle;
+    }
+
+    /// Makes the given button release itself once it's been held for
+    /// `duration` seconds, as counted by `update()`, even if whatever
+    /// is holding it stays down.  It can't be pressed again until
+    /// that's let go.  0 turns this off, which is the default.
+    pub fn set_button_oneshot(&mut self, button: Buttons, duration: f64) {
+        let button_status = self.buttons.entry(button).or_insert_with(ButtonStatus::default);
+        button_status.oneshot = duration;
+    }
+
+    /// Turns on double tap detection for the given button: a second
+    /// press within `window` seconds of the first is a double tap.
+    /// If it comes later than that, it starts a new window instead.
+    pub fn double_tap_window(&mut self, button: Buttons, window: f64) {
+        let button_status = self.buttons.entry(button).or_insert_with(ButtonStatus::default);
+        button_status.double_tap_window = window;
+        button_status.last_press_time = None;
+    }
+
+    /// Returns true if the given button was double tapped since
+    /// the last call to `update()`.
+    pub fn get_double_tap(&self, button: Buttons) -> bool {
+        if let Some(button_status) = self.buttons.get(&button) {
+            button_status.queried.set(true);
+            button_status.double_tapped
+        } else {
+            false
+        }
+    }
+
+    pub fn get_button_down(&self, axis: Buttons) -> bool {
+        self.get_button(axis)
+    }
+
+    pub fn get_button_up(&self, axis: Buttons) -> bool {
+        !self.get_button(axis)
+    }
+
+    /// Returns true if the button went down since the last
+    /// call to `update()`.
+    pub fn get_button_pressed(&self, button: Buttons) -> bool {
+        if let Some(button_status) = self.buttons.get(&button) {
+            button_status.queried.set(true);
+            button_status.pressed_edge
+        } else {
+            false
+        }
+    }
+
+    /// Returns true if the button went up since the last
+    /// call to `update()`.
+    pub fn get_button_released(&self, button: Buttons) -> bool {
+        if let Some(button_status) = self.buttons.get(&button) {
+            button_status.queried.set(true);
+            button_status.released_edge
+        } else {
+            false
+        }
+    }
+
+    /// Iterates over all the logical buttons that are currently
+    /// held down, in no particular order.
+    pub fn pressed_buttons<'a>(&'a self) -> impl Iterator<Item = Buttons> + 'a {
+        self.buttons
+            .iter()
+            .filter(|&(_, button_status)| button_status.pressed)
+            .map(|(button, _)| button.clone())
+    }
+
+    /// Returns true if any logical button is held down.
+    pub fn any_button_pressed(&self) -> bool {
+        self.buttons.values().any(|button_status| button_status.pressed)
+    }
+
+    /// Returns true if any bound input, whether for a button or an
+    /// axis, was pressed since the last `update()`, such as for
+    /// "press any key to continue" screens.
+    pub fn any_input_this_frame(&self) -> bool {
+        self.any_input
+    }
+
+    /// Returns true if the given key is physically held down,
+    /// whatever it's bound to.  Disabling keys, text input and replays
+    /// don't affect this, so it's good for hard-coded debug keys.
+    pub fn is_key_pressed(&self, keycode: Key) -> bool {
+        self.pressed_keys.contains(&keycode)
+    }
+
+    /// Returns the last cursor position given to
+    /// `update_mouse_position()`.
+    pub fn mouse_position(&self) -> (i32, i32) {
+        self.mouse_position
+    }
+
+    /// Returns how far the cursor has moved since the last
+    /// call to `update()`.
+    pub fn mouse_position_delta(&self) -> (i32, i32) {
+        let (x, y) = self.mouse_position;
+        let (lx, ly) = self.last_mouse_position;
+        (x - lx, y - ly)
+    }
+
+    /// Returns how far the mouse wheel has scrolled since the
+    /// last call to `update()`.
+    pub fn mouse_scroll_delta(&self) -> (f32, f32) {
+        self.mouse_scroll_delta
+    }
+
+    /// Returns true if any logical button bound to the
+    /// given mouse button is pressed.
+    pub fn get_mouse_button(&self, button: MouseButton) -> bool {
+        match self.lookup_effects(&InputEvent::MouseButtonEvent(button)) {
+            Some(effects) => {
+                effects.iter().any(|effect| match *effect {
+                    InputEffect::Button(ref logical) => self.get_button(logical.clone()),
+                    _ => false,
+                })
+            }
+            None => false,
+        }
+    }
+
+    pub fn get_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.get_mouse_button(button)
+    }
+
+    pub fn get_mouse_button_up(&self, button: MouseButton) -> bool {
+        !self.get_mouse_button(button)
+    }
+
+    pub fn reset_input_axes(&mut self) {
+        for (_axis, axis_status) in self.axes.iter_mut() {
+            axis_status.reset();
+        }
+    }
+
+    /// Like `reset_input_axes()`, but for just the given axis.
+    pub fn reset_axis(&mut self, axis: Axes) {
+        if let Some(axis_status) = self.axes.get_mut(&axis) {
+            axis_status.reset();
+        }
+    }
+
+    /// Releases all buttons, without any released edges, and forgets
+    /// any buffered presses and held times.  Settings like the buffer
+    /// length are kept.
+    pub fn reset_buttons(&mut self) {
+        for (_button, button_status) in self.buttons.iter_mut() {
+            button_status.reset();
+        }
+    }
+
+    /// Like `reset_buttons()`, but for just the given button.
+    pub fn reset_button(&mut self, button: Buttons) {
+        if let Some(button_status) = self.buttons.get_mut(&button) {
+            button_status.reset();
+        }
+    }
+
+    /// Captures the positions and directions of all the axes and
+    /// whether each button is down, such as to roll back to later.
+    pub fn snapshot(&self) -> InputSnapshot<Axes, Buttons> {
+        let axes = self.axes
+            .iter()
+            .map(|(axis, status)| (axis.clone(), (status.position, status.direction)))
+            .collect();
+        let buttons = self.buttons
+            .iter()
+            .map(|(button, status)| (button.clone(), status.pressed))
+            .collect();
+        InputSnapshot {
+            axes: axes,
+            buttons: buttons,
+        }
+    }
+
+    /// Puts the axes and buttons back how they were in the snapshot.
+    /// This sets the logical state only, without any edges; keys and
+    /// such that are still held carry on as before.
+    pub fn restore(&mut self, snap: &InputSnapshot<Axes, Buttons>) {
+        for (axis, &(position, direction)) in &snap.axes {
+            let axis_status = self.axes.entry(axis.clone()).or_insert_with(AxisStatus::default);
+            axis_status.analog = false;
+            axis_status.analog_value = 0.0;
+            axis_status.set_position(position);
+            axis_status.direction = direction;
+            axis_status.last_position = position;
+            axis_status.velocity = 0.0;
+        }
+        for (button, &pressed) in &snap.buttons {
+            let button_status = self.buttons
+                .entry(button.clone())
+                .or_insert_with(ButtonStatus::default);
+            button_status.pressed = pressed;
+            button_status.pressed_edge = false;
+            button_status.released_edge = false;
+        }
+    }
+
+    /// Returns what's changed between the snapshot and now, so
+    /// only the differences need sending somewhere.  Axes and
+    /// buttons missing from one side count as at rest.
+    pub fn diff(&self, other: &InputSnapshot<Axes, Buttons>) -> Vec<InputChange<Axes, Buttons>> {
+        let current = self.snapshot();
+        let mut changes = Vec::new();
+        for (axis, &(position, direction)) in &current.axes {
+            if other.axes.get(axis).cloned().unwrap_or((0.0, 0.0)) != (position, direction) {
+                changes.push(InputChange::Axis(axis.clone(), position, direction));
+            }
+        }
+        for axis in other.axes.keys() {
+            if !current.axes.contains_key(axis) {
+                changes.push(InputChange::Axis(axis.clone(), 0.0, 0.0));
+            }
+        }
+        for (button, &pressed) in &current.buttons {
+            if other.buttons.get(button).cloned().unwrap_or(false) != pressed {
+                changes.push(InputChange::Button(button.clone(), pressed));
+            }
+        }
+        for button in other.buttons.keys() {
+            if !current.buttons.contains_key(button) {
+                changes.push(InputChange::Button(button.clone(), false));
+            }
+        }
+        changes
+    }
+
+    /// Sets which axes and buttons `encode_frame()` packs, and in
+    /// what order.  Every peer has to use the same layout.
+    pub fn set_frame_layout(&mut self, axes: Vec<Axes>, buttons: Vec<Buttons>) {
+        self.frame_axes = axes;
+        self.frame_buttons = buttons;
+    }
+
+    /// Packs the current input into a few bytes, for lockstep
+    /// networking.  Using the order from `set_frame_layout()`, the
+    /// buttons come first as one bit each, whether they're down,
+    /// lowest bit first and padded out to a whole byte.  Then comes
+    /// one byte per axis: its position as an i8, from -127 for -1 to
+    /// 127 for 1.  Positions outside of that get clamped.  None of
+    /// this depends on the platform, so it's safe to hash or send.
+    pub fn encode_frame(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; (self.frame_buttons.len() + 7) / 8];
+        for (i, button) in self.frame_buttons.iter().enumerate() {
+            if self.get_button(button.clone()) {
+                bytes[i / 8] |= 1 << (i % 8);
+            }
+        }
+        for axis in &self.frame_axes {
+            let position = self.axes.get(axis).map_or(0.0, |axis_status| axis_status.position);
+            let quantized = (f64::max(-1.0, f64::min(1.0, position)) * 127.0).round() as i8;
+            bytes.push(quantized as u8);
+        }
+        bytes
+    }
+
+    /// Sets the buttons and axes to what a frame from
+    /// `encode_frame()` says, such as one from another player.
+    /// Buttons that change get their edges like a real press or
+    /// release would, though without running any callbacks.  The
+    /// axes stay where they're put until something else moves them,
+    /// like with analog input.  If the frame is the wrong size for the
+    /// layout, nothing changes and this returns an error.
+    pub fn decode_and_apply_frame(&mut self, bytes: &[u8]) -> Result<(), FrameSizeError> {
+        let button_bytes = (self.frame_buttons.len() + 7) / 8;
+        let expected = button_bytes + self.frame_axes.len();
+        if bytes.len() != expected {
+            return Err(FrameSizeError {
+                expected: expected,
+                found: bytes.len(),
+            });
+        }
+        for (i, button) in self.frame_buttons.iter().enumerate() {
+            let pressed = bytes[i / 8] & (1 << (i % 8)) != 0;
+            let button_status = self.buttons
+                .entry(button.clone())
+                .or_insert_with(ButtonStatus::default);
+            if pressed != button_status.pressed {
+                button_status.pressed_edge = pressed;
+                button_status.released_edge = !pressed;
+            }
+            button_status.pressed = pressed;
+        }
+        for (axis, &byte) in self.frame_axes.iter().zip(&bytes[button_bytes..]) {
+            let position = byte as i8 as f64 / 127.0;
+            let axis_status = self.axes.entry(axis.clone()).or_insert_with(AxisStatus::default);
+            axis_status.analog = true;
+            axis_status.analog_value = position;
+            axis_status.set_position(position);
+        }
+        Ok(())
+    }
+}
+
+// Shortcuts for driving a manager with mock keys in tests.
+#[cfg(any(test, feature = "mock-input"))]
+impl<Axes, Buttons> InputManager<Axes, Buttons, MockKey>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone
+{
+    /// Presses the given key, without modifiers.
+    pub fn mock_press(&mut self, key: char) {
+        self.update_keydown(Some(MockKey(key)), Mod::empty(), false);
+    }
+
+    /// Releases the given key.
+    pub fn mock_release(&mut self, key: char) {
+        self.update_keyup(Some(MockKey(key)), Mod::empty());
+    }
+
+    /// Presses and releases the given key.
+    pub fn mock_tap(&mut self, key: char) {
+        self.mock_press(key);
+        self.mock_release(key);
+    }
+}
+
+// Describing bindings as text needs to be able to print them.
+impl<Axes, Buttons, Key> InputManager<Axes, Buttons, Key>
+    where Axes: Eq + Hash + Clone + fmt::Debug,
+          Buttons: Eq + Hash + Clone + fmt::Debug,
+          Key: Eq + Hash + Clone + fmt::Debug
+{
+    /// Returns a description of the current key bindings, one per
+    /// line and sorted, like `Up -> Axis(Vert, +)` or `Z -> Button(A)`,
+    /// such as for players to paste into bug reports.  Bindings with
+    /// modifiers list them after the key, and axis bindings that push
+    /// only part way say how far, like `Left -> Axis(Horz, -0.5)`.
+    pub fn describe_bindings(&self) -> String {
+        let mut lines: Vec<String> = self.bindings_with_mods_iter()
+            .map(|(keycode, mods, effect)| {
+                let input = if mods.is_empty() {
+                    format!("{:?}", keycode)
+                } else {
+                    format!("{:?} {:?}", keycode, mods)
+                };
+                match effect {
+                    BoundEffect::Axis(axis, value) => {
+                        let sign = if value >= 0.0 { "+" } else { "-" };
+                        let magnitude = f64::abs(value);
+                        if magnitude == 1.0 {
+                            format!("{} -> Axis({:?}, {})", input, axis, sign)
+                        } else {
+                            format!("{} -> Axis({:?}, {}{})", input, axis, sign, magnitude)
+                        }
+                    }
+                    BoundEffect::Button(button) => format!("{} -> Button({:?})", input, button),
+                }
+            })
+            .collect();
+        lines.sort();
+        lines.join("\n")
+    }
+}
+
+
+impl<Axes, Buttons, Key> Default for InputManager<Axes, Buttons, Key>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone,
+          Key: Eq + Hash + Clone
+{
+    fn default() -> Self {
+        InputManager::new()
+    }
+}
+
+// The default bindings and saving and loading bindings by key
+// name only make sense with real keycodes.
+impl<Axes, Buttons> InputManager<Axes, Buttons, Keycode>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone
+{
+    /// Makes a new manager with the default bindings described at the
+    /// top of this module: WASD and the arrow keys for movement (see
+    /// `bind_wasd()`), plus three action buttons.  `button1` is bound
+    /// to Enter, Z and the left mouse button, `button2` to Shift, X
+    /// and the middle mouse button, and `button3` to Ctrl, C and
+    /// the right mouse button.
+    pub fn with_default_bindings(x_axis: Axes,
+                                 y_axis: Axes,
+                                 button1: Buttons,
+                                 button2: Buttons,
+                                 button3: Buttons)
+                                 -> Self {
+        InputManager::new()
+            .bind_wasd(x_axis, y_axis)
+            .bind_key_to_button(Keycode::Return, button1.clone())
+            .bind_key_to_button(Keycode::Z, button1.clone())
+            .bind_mouse_to_button(MouseButton::Left, button1)
+            .bind_key_to_button(Keycode::LShift, button2.clone())
+            .bind_key_to_button(Keycode::RShift, button2.clone())
+            .bind_key_to_button(Keycode::X, button2.clone())
+            .bind_mouse_to_button(MouseButton::Middle, button2)
+            .bind_key_to_button(Keycode::LCtrl, button3.clone())
+            .bind_key_to_button(Keycode::RCtrl, button3.clone())
+            .bind_key_to_button(Keycode::C, button3.clone())
+            .bind_mouse_to_button(MouseButton::Right, button3)
+    }
+
+    /// Binds W and the up arrow to +Y on `y_axis`, S and down to -Y,
+    /// D and right to +X on `x_axis`, and A and left to -X.
+    pub fn bind_wasd(self, x_axis: Axes, y_axis: Axes) -> Self {
+        self.bind_key_to_axis(Keycode::W, y_axis.clone(), true)
+            .bind_key_to_axis(Keycode::Up, y_axis.clone(), true)
+            .bind_key_to_axis(Keycode::S, y_axis.clone(), false)
+            .bind_key_to_axis(Keycode::Down, y_axis, false)
+            .bind_key_to_axis(Keycode::D, x_axis.clone(), true)
+            .bind_key_to_axis(Keycode::Right, x_axis.clone(), true)
+            .bind_key_to_axis(Keycode::A, x_axis.clone(), false)
+            .bind_key_to_axis(Keycode::Left, x_axis, false)
+    }
+
+    /// Returns a copy of all the key bindings, which can be
+    /// serialized to save them.
+    pub fn export_bindings(&self) -> Bindings<Axes, Buttons> {
+        let keys = self.bindings_with_mods_iter()
+            .map(|(keycode, mods, effect)| {
+                KeyBinding {
+                    key: keycode.name(),
+                    mods: mods.bits(),
+                    effect: effect,
+                }
+            })
+            .collect();
+        Bindings { keys: keys }
+    }
+
+    /// Replaces all the key bindings with the given ones,
+    /// such as ones deserialized from a config file.
+    /// Mouse bindings are left alone.  Key names that
+    /// don't match any keycode are skipped, and returned so the
+    /// game can warn about them.
+    pub fn import_bindings(&mut self, bindings: Bindings<Axes, Buttons>) -> Vec<String> {
+        self.remove_key_bindings();
+        let mut unknown = Vec::new();
+        for binding in bindings.keys {
+            if let Some(keycode) = Keycode::from_name(&binding.key) {
+                let event = InputEvent::KeyEvent(keycode, Mod::from_bits_truncate(binding.mods));
+                self.add_binding(event, binding.effect.into());
+            } else {
+                unknown.push(binding.key);
+            }
+        }
+        unknown
+    }
+
+    /// Makes a new input manager set up from the given profile.  Keys
+    /// whose names aren't known get skipped; use `apply_profile()` to
+    /// find out which.
+    pub fn from_profile(profile: InputProfile<Axes, Buttons>) -> Self {
+        let mut im = InputManager::new();
+        im.apply_profile(profile);
+        im
+    }
+
+    /// Sets up the key bindings and axis settings from the given
+    /// profile, such as one a player picked.  A profile only needs
+    /// what it changes, and everything else stays as it is: if it has
+    /// bindings, they replace all the key bindings like with
+    /// `import_bindings()`, though mouse and controller bindings are
+    /// kept; otherwise the bindings aren't touched.  Only the axes it
+    /// lists change, and only in the settings they give.  Returns the
+    /// names of any keys it skipped, like `import_bindings()`.
+    pub fn apply_profile(&mut self, profile: InputProfile<Axes, Buttons>) -> Vec<String> {
+        let unknown = match profile.bindings {
+            Some(bindings) => self.import_bindings(bindings),
+            None => Vec::new(),
+        };
+        for (axis, settings) in profile.axes {
+            if let Some(sensitivity) = settings.sensitivity {
+                self.set_axis_sensitivity(axis.clone(), sensitivity);
+            }
+            if let Some(inverted) = settings.inverted {
+                self.set_axis_inverted(axis.clone(), inverted);
+            }
+            if let Some(deadzone) = settings.deadzone {
+                self.set_axis_deadzone(axis, deadzone);
+            }
+        }
+        unknown
+    }
+
+    /// Returns a profile with the given name holding all the current
+    /// key bindings and the settings of every axis, which
+    /// `apply_profile()` can restore later.
+    pub fn export_profile(&self, name: &str) -> InputProfile<Axes, Buttons> {
+        let axes = self.axes
+            .iter()
+            .map(|(axis, status)| {
+                let settings = AxisSettings {
+                    sensitivity: Some(status.sensitivity),
+                    inverted: Some(status.inverted),
+                    deadzone: Some(status.deadzone),
+                };
+                (axis.clone(), settings)
+            })
+            .collect();
+        InputProfile {
+            name: name.to_string(),
+            bindings: Some(self.export_bindings()),
+            axes: axes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ggez::event::*;
+    use super::*;
+
+    #[derive(Hash, Eq, PartialEq, Copy, Clone, Debug)]
+    enum Buttons {
+        A,
+        B,
+        Select,
+        Start,
+    }
+
+    #[derive(Hash, Eq, PartialEq, Copy, Clone, Debug)]
+    enum Axes {
+        Horz,
+        Vert,
+    }
+    // Presses and releases a key.
+    fn tap(im: &mut InputManager<Axes, Buttons>, key: Keycode) {
+        im.update_keydown(Some(key), Mod::empty(), false);
+        im.update_keyup(Some(key), Mod::empty());
+    }
+
+    #[test]
+    fn test_input_events() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::B)
+            .bind_key_to_button(Keycode::Return, Buttons::Start)
+            .bind_key_to_button(Keycode::RShift, Buttons::Select)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
+            .bind_key_to_axis(Keycode::Down, Axes::Vert, false)
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false)
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true);
+
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert!(im.get_button(Buttons::A));
+        assert!(im.get_button_down(Buttons::A));
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        assert!(!im.get_button(Buttons::A));
+        assert!(im.get_button_up(Buttons::A));
+
+        // Push the 'up' button, watch the axis
+        // increase to 1.0 but not beyond
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        assert!(im.get_axis_raw(Axes::Vert) > 0.0);
+        while im.get_axis(Axes::Vert) < 0.99 {
+            im.update(0.16);
+            assert!(im.get_axis(Axes::Vert) >= 0.0);
+            assert!(im.get_axis(Axes::Vert) <= 1.0);
+        }
+        // Release it, watch it wind down
+        im.update_keyup(Some(Keycode::Up), Mod::empty());
+        while im.get_axis(Axes::Vert) > 0.01 {
+            im.update(0.16);
+            assert!(im.get_axis(Axes::Vert) >= 0.0)
+        }
+
+        // Do the same with the 'down' button.
+        im.update_keydown(Some(Keycode::Down), Mod::empty(), false);
+        while im.get_axis(Axes::Vert) > -0.99 {
+            im.update(0.16);
+            assert!(im.get_axis(Axes::Vert) <= 0.0);
+            assert!(im.get_axis(Axes::Vert) >= -1.0);
+        }
+    }
+
+    #[test]
+    fn test_mouse_buttons() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_mouse_to_button(MouseButton::Left, Buttons::A)
+            .bind_mouse_to_button(MouseButton::Right, Buttons::B);
+
+        im.update_mousedown(MouseButton::Left);
+        assert!(im.get_button(Buttons::A));
+        assert!(im.get_mouse_button(MouseButton::Left));
+        assert!(im.get_mouse_button_down(MouseButton::Left));
+        assert!(im.get_mouse_button_up(MouseButton::Right));
+        im.update_mouseup(MouseButton::Left);
+        assert!(!im.get_button(Buttons::A));
+        assert!(im.get_mouse_button_up(MouseButton::Left));
+
+        // Unbound mouse buttons do nothing.
+        im.update_mousedown(MouseButton::Middle);
+        assert!(!im.get_mouse_button(MouseButton::Middle));
+    }
+
+    #[test]
+    fn test_opposing_axis_keys() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false)
+            .bind_key_to_axis(Keycode::A, Axes::Horz, false)
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true);
+
+        im.update_keydown(Some(Keycode::Left), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Horz), 0.0);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        assert_eq!(im.get_axis_raw(Axes::Horz), -1.0);
+
+        // Two keys on the same side; releasing one keeps it held.
+        im.update_keydown(Some(Keycode::A), Mod::empty(), false);
+        im.update_keyup(Some(Keycode::Left), Mod::empty());
+        assert_eq!(im.get_axis_raw(Axes::Horz), -1.0);
+        im.update_keyup(Some(Keycode::A), Mod::empty());
+        assert_eq!(im.get_axis_raw(Axes::Horz), 0.0);
+    }
+
+    #[test]
+    fn test_button_edges() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert!(im.get_button_pressed(Buttons::A));
+        assert!(!im.get_button_released(Buttons::A));
+        im.update(0.16);
+        assert!(im.get_button(Buttons::A));
+        assert!(!im.get_button_pressed(Buttons::A));
+
+        // Key repeat shouldn't produce a new edge
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), true);
+        assert!(!im.get_button_pressed(Buttons::A));
+
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        assert!(im.get_button_released(Buttons::A));
+        im.update(0.16);
+        assert!(!im.get_button_released(Buttons::A));
+    }
+
+    #[test]
+    fn test_unbind_key() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
+            .bind_key_to_axis(Keycode::W, Axes::Vert, true);
+
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.unbind_key(Keycode::Up);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 0.0);
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 0.0);
+        im.update_keydown(Some(Keycode::W), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 1.0);
+
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.clear_bindings();
+        // Button state is left alone
+        assert!(im.get_button(Buttons::A));
+        assert_eq!(im.get_axis_raw(Axes::Vert), 0.0);
+        im.update_keydown(Some(Keycode::W), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 0.0);
+
+        // Rebinding works as usual
+        im = im.bind_key_to_button(Keycode::X, Buttons::B);
+        im.update_keydown(Some(Keycode::X), Mod::empty(), false);
+        assert!(im.get_button(Buttons::B));
+    }
+
+    #[test]
+    fn test_binding_for_key() {
+        let im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
+            .bind_mouse_to_button(MouseButton::Left, Buttons::A);
+
+        assert_eq!(im.binding_for_key(Keycode::Up),
+                   Some(BoundEffect::Axis(Axes::Vert, 1.0)));
+        assert_eq!(im.binding_for_key(Keycode::Z), Some(BoundEffect::Button(Buttons::A)));
+        assert_eq!(im.binding_for_key(Keycode::X), None);
+
+        let bindings: Vec<_> = im.bindings_iter().collect();
+        assert_eq!(bindings.len(), 2);
+        assert!(bindings.contains(&(Keycode::Z, BoundEffect::Button(Buttons::A))));
+        assert!(bindings.contains(&(Keycode::Up, BoundEffect::Axis(Axes::Vert, 1.0))));
+    }
+
+    #[test]
+    fn test_export_import_bindings() {
+        let im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
+            .bind_key_to_axis(Keycode::Down, Axes::Vert, false);
+        let exported = im.export_bindings();
+
+        let mut im2 = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::X, Buttons::B)
+            .bind_mouse_to_button(MouseButton::Left, Buttons::B);
+        assert_eq!(im2.import_bindings(exported.clone()), Vec::<String>::new());
+        assert_eq!(im2.binding_for_key(Keycode::X), None);
+        assert_eq!(im2.binding_for_key(Keycode::Z), Some(BoundEffect::Button(Buttons::A)));
+        assert_eq!(im2.binding_for_key(Keycode::Up),
+                   Some(BoundEffect::Axis(Axes::Vert, 1.0)));
+        assert_eq!(im2.binding_for_key(Keycode::Down),
+                   Some(BoundEffect::Axis(Axes::Vert, -1.0)));
+        assert_eq!(im2.bindings_iter().count(), 3);
+
+        im2.update_mousedown(MouseButton::Left);
+        assert!(im2.get_button(Buttons::B));
+        im2.update_keydown(Some(Keycode::Down), Mod::empty(), false);
+        assert_eq!(im2.get_axis_raw(Axes::Vert), -1.0);
+
+        let mut bindings = im.export_bindings();
+        bindings.keys.push(KeyBinding {
+            key: "NotAKey".to_string(),
+            mods: 0,
+            effect: BoundEffect::Button(Buttons::B),
+        });
+        assert_eq!(im2.import_bindings(bindings), vec!["NotAKey".to_string()]);
+        assert_eq!(im2.bindings_iter().count(), 3);
+    }
+
+    #[test]
+    fn test_rebind() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::B);
+
+        im.begin_rebind(BoundEffect::Button(Buttons::Start));
+        assert!(im.rebind_pending());
+        im.update_keydown(Some(Keycode::Return), Mod::empty(), false);
+        assert!(!im.rebind_pending());
+        // The captured key doesn't also fire its new binding.
+        assert!(!im.get_button(Buttons::Start));
+        assert_eq!(im.take_displaced_bindings(), vec![]);
+        im.update_keyup(Some(Keycode::Return), Mod::empty());
+        im.update_keydown(Some(Keycode::Return), Mod::empty(), false);
+        assert!(im.get_button(Buttons::Start));
+
+        im.begin_rebind(BoundEffect::Axis(Axes::Horz, 1.0));
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert!(!im.get_button(Buttons::A));
+        assert_eq!(im.take_displaced_bindings(), vec![BoundEffect::Button(Buttons::A)]);
+        assert_eq!(im.binding_for_key(Keycode::Z),
+                   Some(BoundEffect::Axis(Axes::Horz, 1.0)));
+
+        im.begin_rebind(BoundEffect::Button(Buttons::Select));
+        im.cancel_rebind();
+        im.update_keydown(Some(Keycode::X), Mod::empty(), false);
+        assert!(im.get_button(Buttons::B));
+    }
+
+    #[test]
+    fn test_axis_tween() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis_with_tween(Keycode::Right, Axes::Horz, true, 10.0, 1.0)
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.05);
+        assert_eq!(im.get_axis(Axes::Horz), 0.5);
+        assert_eq!(im.get_axis(Axes::Vert), 0.2);
+
+        im.set_axis_tween(Axes::Vert, 2.0, 10.0);
+        im.update_keyup(Some(Keycode::Up), Mod::empty());
+        im.update(0.01);
+        assert!((im.get_axis(Axes::Vert) - 0.1).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_axis_deadzone() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_axis_deadzone(Axes::Vert, 0.5);
+
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.1);
+        assert_eq!(im.get_axis(Axes::Vert), 0.0);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 1.0);
+        im.update(0.1);
+        assert!(im.get_axis(Axes::Vert) > 0.5);
+    }
+
+    #[test]
+    fn test_axis_inverted() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_axis_inverted(Axes::Vert, true);
+        // Binding more keys doesn't reset it
+        im = im.bind_key_to_axis(Keycode::Down, Axes::Vert, false);
+
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.1);
+        assert!(im.get_axis(Axes::Vert) < 0.0);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 1.0);
+        im.set_axis_inverted(Axes::Vert, false);
+        assert!(im.get_axis(Axes::Vert) > 0.0);
+    }
+
+    #[test]
+    fn test_axis_sensitivity() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis_with_tween(Keycode::Up, Axes::Vert, true, 100.0, 100.0);
+        im.set_axis_sensitivity(Axes::Vert, 2.5);
+
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.1);
+        assert_eq!(im.get_axis(Axes::Vert), 2.5);
+        im.set_axis_sensitivity_clamped(Axes::Vert, true);
+        assert_eq!(im.get_axis(Axes::Vert), 1.0);
+        im.set_axis_inverted(Axes::Vert, true);
+        assert_eq!(im.get_axis(Axes::Vert), -1.0);
+    }
+
+    #[test]
+    fn test_axis_snap() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_axis_snap(Axes::Horz, true);
+
+        im.update_keydown(Some(Keycode::Left), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        assert_eq!(im.get_axis(Axes::Horz), -1.0);
+        assert_eq!(im.get_axis(Axes::Vert), 0.0);
+        im.update(0.1);
+        assert_eq!(im.get_axis(Axes::Horz), -1.0);
+        assert!(im.get_axis(Axes::Vert) < 1.0);
+        im.update_keyup(Some(Keycode::Left), Mod::empty());
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+    }
+
+    #[test]
+    fn test_axes_vector() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_axis_snap(Axes::Horz, true);
+        im.set_axis_snap(Axes::Vert, true);
+
+        assert_eq!(im.get_axes_vector_normalized(Axes::Horz, Axes::Vert), (0.0, 0.0));
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        assert_eq!(im.get_axes_vector_normalized(Axes::Horz, Axes::Vert), (1.0, 0.0));
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        assert_eq!(im.get_axes_vector(Axes::Horz, Axes::Vert), (1.0, 1.0));
+        let (x, y) = im.get_axes_vector_normalized(Axes::Horz, Axes::Vert);
+        assert!((x * x + y * y - 1.0).abs() < 1e-10);
+        assert_eq!(x, y);
+    }
+
+    #[test]
+    fn test_multiple_effects() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::Z, Buttons::B)
+            .bind_key_to_axis(Keycode::Z, Axes::Horz, true)
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+        assert_eq!(im.bindings_for_key(Keycode::Z),
+                   vec![BoundEffect::Button(Buttons::A),
+                        BoundEffect::Button(Buttons::B),
+                        BoundEffect::Axis(Axes::Horz, 1.0)]);
+
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert!(im.get_button(Buttons::A));
+        assert!(im.get_button(Buttons::B));
+        assert_eq!(im.get_axis_raw(Axes::Horz), 1.0);
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        assert!(!im.get_button(Buttons::A));
+        assert!(!im.get_button(Buttons::B));
+        assert_eq!(im.get_axis_raw(Axes::Horz), 0.0);
+    }
+
+    #[test]
+    fn test_controller_buttons() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Return, Buttons::Start)
+            .bind_controller_to_button(0, Button::Start, Buttons::Start)
+            .bind_controller_to_button(0, Button::A, Buttons::A);
+
+        im.update_controller_button_down(0, Button::Start);
+        assert!(im.get_button(Buttons::Start));
+        im.update_controller_button_up(0, Button::Start);
+        assert!(!im.get_button(Buttons::Start));
+        im.update_keydown(Some(Keycode::Return), Mod::empty(), false);
+        assert!(im.get_button(Buttons::Start));
+        im.update_controller_button_down(1, Button::A);
+        assert!(!im.get_button(Buttons::A));
+        im.update_controller_button_down(0, Button::A);
+        assert!(im.get_button(Buttons::A));
+    }
+
+    #[test]
+    fn test_per_player() {
+        let mut players = InputManager::<Axes, Buttons>::per_player(2, |im, player| {
+            im.bind_controller_to_button(player, Button::A, Buttons::A)
+                .bind_controller_axis_to_axis(player, Axis::LeftX, Axes::Horz)
+        });
+        assert_eq!(players.len(), 2);
+
+        for im in players.iter_mut() {
+            im.update_controller_button_down(1, Button::A);
+            im.update_controller_axis(0, Axis::LeftX, i16::MAX);
+        }
+        assert!(!players[0].get_button(Buttons::A));
+        assert!(players[1].get_button(Buttons::A));
+        assert_eq!(players[0].get_axis(Axes::Horz), 1.0);
+        assert_eq!(players[1].get_axis(Axes::Horz), 0.0);
+    }
+
+    #[test]
+    fn test_analog_axes() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_controller_axis_to_axis(0, Axis::LeftX, Axes::Horz)
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true);
+        im.set_axis_deadzone(Axes::Horz, 0.1);
+
+        im.update_controller_axis(0, Axis::LeftX, i16::MIN);
+        assert_eq!(im.get_axis(Axes::Horz), -1.0);
+        im.update_controller_axis(0, Axis::LeftX, 16384);
+        im.update(0.5);
+        // No gravity pulling it back
+        assert!((im.get_axis(Axes::Horz) - 0.5).abs() < 0.001);
+        im.update_axis_analog(Axes::Horz, 0.05);
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+
+        // A key press takes over and tweens the axis again.
+        im.update_axis_analog(Axes::Horz, 0.5);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(0.05);
+        assert!((im.get_axis(Axes::Horz) - 0.7).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_key_mods() {
+        // KMOD_LCTRL and KMOD_LSHIFT
+        let ctrl = Mod::from_bits_truncate(0x0040);
+        let shift = Mod::from_bits_truncate(0x0001);
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::S, Buttons::A)
+            .bind_key_mod_to_button(Keycode::S, ctrl, Buttons::Start);
+
+        im.update_keydown(Some(Keycode::S), ctrl, false);
+        assert!(im.get_button(Buttons::Start));
+        assert!(!im.get_button(Buttons::A));
+        // Letting go of ctrl first still releases the ctrl binding
+        im.update_keyup(Some(Keycode::S), Mod::empty());
+        assert!(!im.get_button(Buttons::Start));
+
+        // Unbound modifiers fall back to the plain binding
+        im.update_keydown(Some(Keycode::S), shift, false);
+        assert!(im.get_button(Buttons::A));
+        assert!(!im.get_button(Buttons::Start));
+        // Pressing ctrl while S is held doesn't switch bindings
+        im.update_keydown(Some(Keycode::S), shift | ctrl, false);
+        assert!(!im.get_button(Buttons::Start));
+        // and neither does a key repeat with it held
+        im.update_keydown(Some(Keycode::S), shift | ctrl, true);
+        assert!(!im.get_button(Buttons::Start));
+        assert!(im.get_button(Buttons::A));
+        im.update_keyup(Some(Keycode::S), shift | ctrl);
+        assert!(!im.get_button(Buttons::A));
+
+        assert_eq!(im.bindings_iter().count(), 1);
+        assert_eq!(im.bindings_with_mods_iter().count(), 2);
+    }
+
+    #[test]
+    fn test_chords() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_chord_to_button(vec![Keycode::Right, Keycode::Space], Buttons::B);
+
+        im.update_keydown(Some(Keycode::Space), Mod::empty(), false);
+        assert!(!im.get_button(Buttons::B));
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        assert!(im.get_button(Buttons::B));
+        assert_eq!(im.get_axis_raw(Axes::Horz), 1.0);
+        im.update_keyup(Some(Keycode::Space), Mod::empty());
+        assert!(!im.get_button(Buttons::B));
+        assert_eq!(im.get_axis_raw(Axes::Horz), 1.0);
+        im.update_keydown(Some(Keycode::Space), Mod::empty(), false);
+        assert!(im.get_button(Buttons::B));
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        assert!(!im.get_button(Buttons::B));
+    }
+
+    #[test]
+    fn test_sequences() {
+        let mut im = InputManager::<Axes, Buttons>::new();
+        im.register_sequence(Buttons::Select,
+                             vec![Keycode::Down, Keycode::Right, Keycode::Z],
+                             0.2);
+
+        tap(&mut im, Keycode::Down);
+        im.update(0.1);
+        tap(&mut im, Keycode::Right);
+        im.update(0.1);
+        assert!(!im.sequence_triggered(Buttons::Select));
+        tap(&mut im, Keycode::Z);
+        assert!(im.sequence_triggered(Buttons::Select));
+        im.update(0.1);
+        assert!(!im.sequence_triggered(Buttons::Select));
+
+        // Too slow
+        tap(&mut im, Keycode::Down);
+        im.update(0.3);
+        tap(&mut im, Keycode::Right);
+        tap(&mut im, Keycode::Z);
+        assert!(!im.sequence_triggered(Buttons::Select));
+
+        // Wrong order
+        tap(&mut im, Keycode::Right);
+        tap(&mut im, Keycode::Down);
+        tap(&mut im, Keycode::Z);
+        assert!(!im.sequence_triggered(Buttons::Select));
+    }
+
+    #[test]
+    fn test_button_buffer() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::B);
+        im.set_button_buffer(Buttons::A, 0.1);
+
+        tap(&mut im, Keycode::Z);
+        im.update(0.05);
+        im.update(0.04);
+        assert!(im.consume_button_press(Buttons::A));
+        assert!(!im.consume_button_press(Buttons::A));
+
+        tap(&mut im, Keycode::Z);
+        im.update(0.05);
+        im.update(0.06);
+        assert!(!im.consume_button_press(Buttons::A));
+
+        // Without a buffer it lasts until the next update
+        tap(&mut im, Keycode::X);
+        assert!(im.consume_button_press(Buttons::B));
+        tap(&mut im, Keycode::X);
+        im.update(0.01);
+        assert!(!im.consume_button_press(Buttons::B));
+    }
+
+    #[test]
+    fn test_button_held_duration() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+
+        assert_eq!(im.button_held_duration(Buttons::A), 0.0);
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update(0.25);
+        im.update(0.25);
+        assert_eq!(im.button_held_duration(Buttons::A), 0.5);
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        assert_eq!(im.button_held_duration(Buttons::A), 0.0);
+        im.update(0.25);
+        assert_eq!(im.button_held_duration(Buttons::A), 0.0);
+    }
+
+    #[test]
+    fn test_double_tap() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Right, Buttons::A);
+        im.double_tap_window(Buttons::A, 0.3);
+
+        tap(&mut im, Keycode::Right);
+        assert!(!im.get_double_tap(Buttons::A));
+        im.update(0.2);
+        tap(&mut im, Keycode::Right);
+        assert!(im.get_double_tap(Buttons::A));
+        im.update(0.1);
+        assert!(!im.get_double_tap(Buttons::A));
+
+        // Too slow; the second press starts a new window
+        tap(&mut im, Keycode::Right);
+        im.update(0.4);
+        tap(&mut im, Keycode::Right);
+        assert!(!im.get_double_tap(Buttons::A));
+        im.update(0.1);
+        tap(&mut im, Keycode::Right);
+        assert!(im.get_double_tap(Buttons::A));
+    }
+
+    #[test]
+    fn test_contexts() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+        im.push_context("menu", true);
+        im = im.bind_key_to_button(Keycode::Z, Buttons::Start);
+        assert_eq!(im.active_context(), Some("menu"));
+
+        tap(&mut im, Keycode::Up);
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert!(im.get_button(Buttons::Start));
+        assert!(!im.get_button(Buttons::A));
+        assert_eq!(im.get_axis_raw(Axes::Vert), 0.0);
+
+        // Switching releases what was held
+        assert_eq!(im.pop_context(), Some("menu".to_string()));
+        assert_eq!(im.active_context(), None);
+        assert!(!im.get_button(Buttons::Start));
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        tap(&mut im, Keycode::Z);
+        assert!(im.get_button_pressed(Buttons::A));
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 1.0);
+
+        // Axis held in the base context doesn't stay stuck
+        im.push_context("menu", true);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 0.0);
+        assert_eq!(im.binding_for_key(Keycode::Z), Some(BoundEffect::Button(Buttons::Start)));
+    }
+
+    #[test]
+    fn test_context_fallthrough() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+        im.push_context("dialog", false);
+        im.add_key_button(Keycode::Z, Buttons::Start);
+        im.add_key_button(Keycode::X, Buttons::Select);
+
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 1.0);
+        im.update_keyup(Some(Keycode::Up), Mod::empty());
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert!(im.get_button(Buttons::Start));
+        assert!(!im.get_button(Buttons::A));
+        assert_eq!(im.binding_for_key(Keycode::Up), Some(BoundEffect::Axis(Axes::Vert, 1.0)));
+
+        // An opaque context on top blocks everything below.
+        im.push_context("pause", true);
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        assert!(!im.update_keydown(Some(Keycode::Up), Mod::empty(), false));
+        assert!(!im.update_keydown(Some(Keycode::X), Mod::empty(), false));
+        assert_eq!(im.get_axis_raw(Axes::Vert), 0.0);
+        assert!(!im.get_button(Buttons::Select));
+    }
+
+    #[test]
+    fn test_rebind_button() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_keys_to_button(&[Keycode::Z, Keycode::Return], Buttons::A)
+            .bind_key_mod_to_button(Keycode::Z, Mod::from_bits_truncate(1), Buttons::A)
+            .bind_key_to_axis(Keycode::Return, Axes::Horz, true);
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        let mut old = im.rebind_button(Buttons::A, Keycode::Space);
+        old.sort_by_key(|k| *k == Keycode::Return);
+        assert_eq!(old, vec![Keycode::Z, Keycode::Return]);
+        assert!(!im.get_button(Buttons::A));
+        assert_eq!(im.binding_for_key(Keycode::Z), None);
+        assert_eq!(im.bindings_for_key(Keycode::Return),
+                   vec![BoundEffect::Axis(Axes::Horz, 1.0)]);
+        assert_eq!(im.binding_for_key(Keycode::Space), Some(BoundEffect::Button(Buttons::A)));
+    }
+
+    #[test]
+    fn test_disabled_bindings() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Horz), 1.0);
+        im.set_binding_enabled(Keycode::Right, false);
+        assert_eq!(im.get_axis_raw(Axes::Horz), 0.0);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Horz), 0.0);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        assert_eq!(im.binding_for_key(Keycode::Right),
+                   Some(BoundEffect::Axis(Axes::Horz, 1.0)));
+
+        im.set_binding_enabled(Keycode::Right, true);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Horz), 1.0);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.set_all_enabled(false);
+        assert!(!im.get_button(Buttons::A));
+        tap(&mut im, Keycode::Z);
+        assert!(!im.get_button(Buttons::A));
+        im.set_all_enabled(true);
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert!(im.get_button(Buttons::A));
+    }
+
+    #[test]
+    fn test_recording() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+        tap(&mut im, Keycode::Z);
+        assert!(im.take_recording().is_empty());
+
+        im.update(1.0);
+        im.start_recording();
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update(0.5);
+        im.update(0.25);
+        // A negative dt doesn't wind the recording back.
+        im.update(-1.0);
+        im.update_keyup(Some(Keycode::X), Mod::empty());
+        let frames = im.take_recording();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].time, 0.0);
+        assert!(frames[0].down);
+        assert_eq!(frames[1].time, 0.75);
+        assert!(!frames[1].down);
+
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        assert!(im.take_recording().is_empty());
+    }
+
+    #[test]
+    fn test_replay() {
+        let dts = [0.1, 0.05, 0.2, 0.1, 0.15, 0.3];
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false);
+        im.start_recording();
+        let mut recorded = Vec::new();
+        for (i, dt) in dts.iter().enumerate() {
+            match i {
+                0 => im.update_keydown(Some(Keycode::Right), Mod::empty(), false),
+                2 => im.update_keyup(Some(Keycode::Right), Mod::empty()),
+                3 => im.update_keydown(Some(Keycode::Left), Mod::empty(), false),
+                _ => false,
+            };
+            recorded.push((im.get_axis_raw(Axes::Horz), im.get_axis(Axes::Horz)));
+            im.update(*dt);
+        }
+        im.update_keyup(Some(Keycode::Left), Mod::empty());
+        let frames = im.take_recording();
+
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false);
+        im.play_recording(frames);
+        assert!(!im.replay_finished());
+        let mut replayed = Vec::new();
+        for dt in dts.iter() {
+            // Live input gets ignored
+            im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+            replayed.push((im.get_axis_raw(Axes::Horz), im.get_axis(Axes::Horz)));
+            im.update(*dt);
+            // Nor does a negative dt wind the replay back.
+            im.update(-1.0);
+        }
+        assert_eq!(recorded, replayed);
+        assert!(im.replay_finished());
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(0.1);
+        assert!(im.get_axis_raw(Axes::Horz) > 0.0);
+    }
+
+    #[test]
+    fn test_custom_key_type() {
+        #[derive(Hash, Eq, PartialEq, Clone, Debug)]
+        enum Key {
+            Jump,
+            Named(String),
+        }
+        let mut im = InputManager::<Axes, Buttons, Key>::new()
+            .bind_key_to_button(Key::Jump, Buttons::A)
+            .bind_key_to_axis(Key::Named("right".to_string()), Axes::Horz, true);
+
+        im.update_keydown(Some(Key::Jump), Mod::empty(), false);
+        assert!(im.get_button_pressed(Buttons::A));
+        im.update_keyup(Some(Key::Jump), Mod::empty());
+        assert!(!im.get_button(Buttons::A));
+
+        im.update_keydown(Some(Key::Named("right".to_string())), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Horz), 1.0);
+        assert_eq!(im.binding_for_key(Key::Jump), Some(BoundEffect::Button(Buttons::A)));
+    }
+
+    #[test]
+    fn test_mock_keys() {
+        let mut im = MockInputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(MockKey('z'), Buttons::A)
+            .bind_key_to_axis(MockKey('d'), Axes::Horz, true);
+        im.mock_press('d');
+        im.update(0.1);
+        assert!((im.get_axis(Axes::Horz) - 0.4).abs() < 1e-10);
+        im.mock_tap('z');
+        assert!(im.get_button_pressed(Buttons::A));
+        assert!(!im.get_button(Buttons::A));
+    }
+
+    #[test]
+    fn test_unbound_axis() {
+        let im = InputManager::<Axes, Buttons>::new();
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 0.0);
+        assert!(!im.get_button(Buttons::A));
+    }
+
+    #[test]
+    fn test_axis_curve() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_axis_tween(Axes::Horz, 2.0, 2.0);
+        im.set_axis_tween(Axes::Vert, 2.0, 2.0);
+        im.set_axis_curve(Axes::Vert, AxisCurve::Quadratic);
+
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.25);
+        assert_eq!(im.get_axis(Axes::Horz), 0.5);
+        assert_eq!(im.get_axis(Axes::Vert), 0.25);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Vert), 1.0);
+
+        im.update_keyup(Some(Keycode::Up), Mod::empty());
+        im.update(0.25);
+        assert_eq!(im.get_axis(Axes::Vert), 0.25);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Vert), 0.0);
+
+        // Picks up from an analog position without jumping
+        im.update_axis_analog(Axes::Vert, 0.25);
+        im.set_axis_curve(Axes::Vert, AxisCurve::Smoothstep);
+        im.update_keyup(Some(Keycode::Up), Mod::empty());
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.0);
+        assert!(f64::abs(im.get_axis(Axes::Vert) - 0.25) < 1e-9);
+    }
+
+    #[test]
+    fn test_axis_range() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
+            .bind_key_to_axis(Keycode::Down, Axes::Vert, false)
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true);
+        im.set_axis_range(Axes::Vert, 0.0, 1.0);
+        im.set_axis_range(Axes::Horz, 1.0, 3.0);
+        im.set_axis_tween(Axes::Horz, 4.0, 4.0);
+
+        im.update_keydown(Some(Keycode::Down), Mod::empty(), false);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Vert), 0.0);
+        im.update_keyup(Some(Keycode::Down), Mod::empty());
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Vert), 1.0);
+
+        // Rests at the end of the range closest to 0
+        im.update(0.0);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(0.25);
+        assert_eq!(im.get_axis(Axes::Horz), 2.0);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), 3.0);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        im.update(0.25);
+        assert_eq!(im.get_axis(Axes::Horz), 2.0);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+    }
+
+    #[test]
+    fn test_reset_single() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::B);
+        im.set_button_buffer(Buttons::A, 0.5);
+
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.1);
+        im.reset_axis(Axes::Horz);
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+        assert_eq!(im.get_axis_raw(Axes::Horz), 0.0);
+        assert!(im.get_axis(Axes::Vert) > 0.0);
+
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::X), Mod::empty(), false);
+        im.reset_button(Buttons::A);
+        assert!(!im.get_button(Buttons::A));
+        assert!(!im.get_button_pressed(Buttons::A));
+        assert!(!im.consume_button_press(Buttons::A));
+        assert!(im.get_button(Buttons::B));
+        im.reset_buttons();
+        assert!(!im.get_button(Buttons::B));
+
+        // The buffer setting survives
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update(0.2);
+        assert!(im.consume_button_press(Buttons::A));
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+        im.set_paused(true);
+        assert!(im.is_paused());
+
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update(0.1);
+        im.update(0.1);
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+        assert_eq!(im.get_axis_raw(Axes::Horz), 1.0);
+        assert!(im.get_button_pressed(Buttons::A));
+        assert_eq!(im.button_held_duration(Buttons::A), 0.0);
+
+        im.set_paused(false);
+        im.update(0.1);
+        assert!(im.get_axis(Axes::Horz) > 0.0);
+        assert!(!im.get_button_pressed(Buttons::A));
+    }
+
+    #[test]
+    fn test_mouse_scroll() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_scroll_to_button(ScrollDirection::Up, Buttons::A)
+            .bind_scroll_to_button(ScrollDirection::Down, Buttons::B)
+            .bind_scroll_to_axis(ScrollDirection::Up, Axes::Vert, true);
+        im.set_axis_tween(Axes::Vert, 2.0, 1.0);
+
+        im.update_mouse_scroll(0.0, 1.0);
+        im.update_mouse_scroll(1.0, 2.0);
+        assert_eq!(im.mouse_scroll_delta(), (1.0, 3.0));
+        assert!(im.get_button_pressed(Buttons::A));
+        assert!(!im.get_button(Buttons::B));
+        assert_eq!(im.get_axis_raw(Axes::Vert), 1.0);
+
+        im.update(0.25);
+        assert_eq!(im.mouse_scroll_delta(), (0.0, 0.0));
+        assert!(!im.get_button(Buttons::A));
+        assert!(im.get_button_released(Buttons::A));
+        assert_eq!(im.get_axis(Axes::Vert), 0.5);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 0.0);
+
+        im.update_mouse_scroll(0.0, -1.0);
+        assert!(im.get_button_pressed(Buttons::B));
+    }
+
+    #[test]
+    fn test_scroll_notches() {
+        let mut im = InputManager::<Axes, Buttons>::new();
+        im.update_mouse_scroll(0.0, 2.0);
+        im.update_mouse_scroll(0.0, 0.5);
+        assert_eq!(im.scroll_notches(), 2);
+        assert_eq!(im.scroll_notches(), 0);
+        for _ in 0..5 {
+            im.update_mouse_scroll(0.0, 0.25);
+            im.update(0.1);
+        }
+        assert_eq!(im.scroll_notches(), 1);
+
+        // 0.75 is left over from before.
+        im.set_scroll_notch_size(0.5);
+        im.update_mouse_scroll(0.0, -2.0);
+        assert_eq!(im.scroll_notches(), -2);
+        assert_eq!(im.scroll_notches(), 0);
+
+        im.set_scroll_notch_size(0.0);
+        im.set_scroll_notch_size(-1.0);
+        // -0.25 was left over, so this is one notch of 0.5.
+        im.update_mouse_scroll(0.0, 1.0);
+        assert_eq!(im.scroll_notches(), 1);
+    }
+
+    #[test]
+    fn test_mouse_motion() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_mouse_motion_to_axis(MouseAxis::X, Axes::Horz)
+            .bind_mouse_motion_to_axis_with_sensitivity(MouseAxis::Y, Axes::Vert, 0.5);
+        im.set_axis_tween(Axes::Horz, 4.0, 2.0);
+
+        im.update_mouse_motion(25, 1);
+        assert_eq!(im.get_axis(Axes::Horz), 0.25);
+        assert_eq!(im.get_axis(Axes::Vert), 0.5);
+        im.update_mouse_motion(0, -10);
+        assert_eq!(im.get_axis(Axes::Vert), -1.0);
+
+        // Decays instead of holding
+        im.update(0.1);
+        assert!(f64::abs(im.get_axis(Axes::Horz) - 0.05) < 1e-10);
+        im.update(0.1);
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+    }
+
+    #[test]
+    fn test_pressed_buttons() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::B)
+            .bind_key_to_button(Keycode::Return, Buttons::Start);
+        assert!(!im.any_button_pressed());
+        assert_eq!(im.pressed_buttons().count(), 0);
+
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Return), Mod::empty(), false);
+        assert!(im.any_button_pressed());
+        let pressed: Vec<Buttons> = im.pressed_buttons().collect();
+        assert_eq!(pressed.len(), 2);
+        assert!(pressed.contains(&Buttons::A));
+        assert!(pressed.contains(&Buttons::Start));
+    }
+
+    #[test]
+    fn test_button_callbacks() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::A);
+        let presses = Rc::new(Cell::new(0));
+        let releases = Rc::new(Cell::new(0));
+        {
+            let presses = presses.clone();
+            im.on_button_pressed(Buttons::A, Box::new(move || presses.set(presses.get() + 1)));
+            let releases = releases.clone();
+            im.on_button_released(Buttons::A, Box::new(move || releases.set(releases.get() + 1)));
+        }
+
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert_eq!(presses.get(), 1);
+        // Repeats and already held buttons don't call it again
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::X), Mod::empty(), false);
+        assert_eq!(presses.get(), 1);
+        assert_eq!(releases.get(), 0);
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        assert_eq!(releases.get(), 0);
+        im.update_keyup(Some(Keycode::X), Mod::empty());
+        assert_eq!(releases.get(), 1);
+    }
+
+    #[test]
+    fn test_try_bind() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        assert_eq!(im.try_bind_key_to_button(Keycode::Z, Buttons::A), Ok(()));
+        assert_eq!(im.try_bind_key_to_button(Keycode::Z, Buttons::A), Ok(()));
+        assert_eq!(im.try_bind_key_to_button(Keycode::Z, Buttons::B),
+                   Err(BindingConflict { existing: BoundEffect::Button(Buttons::A) }));
+        let err = im.try_bind_key_to_button(Keycode::Up, Buttons::B).unwrap_err();
+        assert_eq!(err.existing, BoundEffect::Axis(Axes::Vert, 1.0));
+        assert_eq!(format!("{}", err), "key is already bound to Axis(Vert, 1.0)");
+        assert_eq!(im.bindings_for_key(Keycode::Z), vec![BoundEffect::Button(Buttons::A)]);
+    }
+
+    #[test]
+    fn test_default_bindings() {
+        let mut im = InputManager::with_default_bindings(Axes::Horz,
+                                                         Axes::Vert,
+                                                         Buttons::A,
+                                                         Buttons::B,
+                                                         Buttons::Start);
+        im.update_keydown(Some(Keycode::W), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Left), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 1.0);
+        assert_eq!(im.get_axis_raw(Axes::Horz), -1.0);
+
+        im.update_keydown(Some(Keycode::Return), Mod::empty(), false);
+        im.update_mousedown(MouseButton::Middle);
+        im.update_keydown(Some(Keycode::C), Mod::empty(), false);
+        assert!(im.get_button(Buttons::A));
+        assert!(im.get_button(Buttons::B));
+        assert!(im.get_button(Buttons::Start));
+    }
+
+    #[test]
+    fn test_text_input() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::W, Axes::Vert, true)
+            .bind_key_to_button(Keycode::Return, Buttons::Start);
+        im.update_text_input('a');
+        assert_eq!(im.take_text_input(), "");
+
+        im.update_keydown(Some(Keycode::W), Mod::empty(), false);
+        im.set_text_input_enabled(true);
+        assert_eq!(im.get_axis_raw(Axes::Vert), 0.0);
+        im.update_keyup(Some(Keycode::W), Mod::empty());
+        im.update_keydown(Some(Keycode::W), Mod::empty(), false);
+        im.update_text_input('w');
+        im.update_text_input('é');
+        assert_eq!(im.get_axis_raw(Axes::Vert), 0.0);
+        assert_eq!(im.take_text_input(), "wé");
+        assert_eq!(im.take_text_input(), "");
+
+        im.set_text_input_suppresses_keys(false);
+        im.update_keydown(Some(Keycode::Return), Mod::empty(), false);
+        assert!(im.get_button(Buttons::Start));
+        im.set_text_input_enabled(false);
+        assert!(!im.text_input_enabled());
+    }
+
+    #[test]
+    fn test_key_repeats() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Down, Buttons::A);
+        im.update_keydown(Some(Keycode::Down), Mod::empty(), false);
+        im.update(0.1);
+        im.update_keydown(Some(Keycode::Down), Mod::empty(), true);
+        assert!(!im.get_button_pressed(Buttons::A));
+
+        im.set_ignore_repeats(false);
+        im.update_keydown(Some(Keycode::Down), Mod::empty(), true);
+        assert!(im.get_button_pressed(Buttons::A));
+        im.update(0.1);
+        assert!(!im.get_button_pressed(Buttons::A));
+        assert!(im.get_button(Buttons::A));
+        im.update_keydown(Some(Keycode::Down), Mod::empty(), true);
+        assert!(im.get_button_pressed(Buttons::A));
+
+        im.update_keyup(Some(Keycode::Down), Mod::empty());
+        assert!(!im.get_button(Buttons::A));
+    }
+
+    #[test]
+    fn test_is_key_pressed() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+        im.update_keydown(Some(Keycode::F1), Mod::empty(), false);
+        assert!(im.is_key_pressed(Keycode::F1));
+        assert!(!im.any_button_pressed());
+
+        im.set_binding_enabled(Keycode::Z, false);
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert!(im.is_key_pressed(Keycode::Z));
+        assert!(!im.get_button(Buttons::A));
+        im.update_keyup(Some(Keycode::F1), Mod::empty());
+        assert!(!im.is_key_pressed(Keycode::F1));
+    }
+
+    #[test]
+    fn test_axis_hold() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
+            .bind_key_to_axis(Keycode::Down, Axes::Vert, false);
+        im.set_axis_hold(Axes::Vert, true);
+        im.set_axis_tween(Axes::Vert, 2.0, 2.0);
+
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.25);
+        im.update_keyup(Some(Keycode::Up), Mod::empty());
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Vert), 0.5);
+        im.update_keydown(Some(Keycode::Down), Mod::empty(), false);
+        im.update(0.125);
+        im.update_keyup(Some(Keycode::Down), Mod::empty());
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Vert), 0.25);
+    }
+
+    #[test]
+    fn test_key_handled() {
+        let ctrl = Mod::from_bits_truncate(0x0040);
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_mod_to_button(Keycode::S, ctrl, Buttons::B)
+            .bind_chord_to_button(vec![Keycode::C, Keycode::D], Buttons::Start);
+        assert!(im.update_keydown(Some(Keycode::Z), Mod::empty(), false));
+        assert!(im.update_keyup(Some(Keycode::Z), Mod::empty()));
+        assert!(!im.update_keydown(Some(Keycode::X), Mod::empty(), false));
+        assert!(!im.update_keyup(Some(Keycode::X), Mod::empty()));
+        assert!(!im.update_keydown(None, Mod::empty(), false));
+
+        assert!(!im.update_keydown(Some(Keycode::S), Mod::empty(), false));
+        im.update_keyup(Some(Keycode::S), Mod::empty());
+        assert!(im.update_keydown(Some(Keycode::S), ctrl, false));
+        assert!(im.update_keydown(Some(Keycode::C), Mod::empty(), false));
+
+        im.set_binding_enabled(Keycode::Z, false);
+        assert!(!im.update_keydown(Some(Keycode::Z), Mod::empty(), false));
+    }
+
+    #[test]
+    fn test_key_handled_layers() {
+        let alt = Mod::from_bits_truncate(0x100);
+        let mut menu = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::W, Buttons::A)
+            .bind_key_to_button(Keycode::F4, Buttons::B);
+        menu.set_text_input_enabled(true);
+        menu.reserve_combo(Keycode::F4, alt);
+        let mut game = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::W, Buttons::A)
+            .bind_key_to_button(Keycode::F4, Buttons::B);
+
+        // Both the menu's text input and its reserved combo should let
+        // the press and the release through to the game.
+        let keys = [(Keycode::W, Mod::empty(), Buttons::A), (Keycode::F4, alt, Buttons::B)];
+        for &(keycode, mods, button) in keys.iter() {
+            if !menu.update_keydown(Some(keycode), mods, false) {
+                game.update_keydown(Some(keycode), mods, false);
+            }
+            assert!(game.get_button(button));
+            if !menu.update_keyup(Some(keycode), mods) {
+                game.update_keyup(Some(keycode), mods);
+            }
+            assert!(!game.get_button(button));
+        }
+    }
+
+    #[test]
+    fn test_axis_blend() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_axis_blend(Axes::Horz, AxisBlend::MaxMagnitude);
+        im.set_axis_blend(Axes::Vert, AxisBlend::Sum);
+        im.set_axis_tween(Axes::Horz, 2.0, 2.0);
+        im.set_axis_tween(Axes::Vert, 2.0, 2.0);
+
+        im.update_axis_analog(Axes::Horz, -0.75);
+        im.update_axis_analog(Axes::Vert, 0.75);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.25);
+        // The stick is still further out than the key has gotten
+        assert_eq!(im.get_axis(Axes::Horz), -0.75);
+        assert_eq!(im.get_axis(Axes::Vert), 1.0);
+        im.update(0.25);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        im.update_keyup(Some(Keycode::Up), Mod::empty());
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), -0.75);
+        assert_eq!(im.get_axis(Axes::Vert), 0.75);
+        im.update_axis_analog(Axes::Vert, 0.0);
+        assert_eq!(im.get_axis(Axes::Vert), 0.0);
+    }
+
+    #[test]
+    fn test_axis_velocity() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_axis_tween(Axes::Horz, 2.0, 4.0);
+        im.set_axis_snap(Axes::Vert, true);
+        assert_eq!(im.get_axis_velocity(Axes::Horz), 0.0);
+
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.25);
+        assert_eq!(im.get_axis_velocity(Axes::Horz), 2.0);
+        assert_eq!(im.get_axis_velocity(Axes::Vert), 4.0);
+        im.update(0.25);
+        assert_eq!(im.get_axis_velocity(Axes::Vert), 0.0);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        im.update(0.125);
+        assert_eq!(im.get_axis_velocity(Axes::Horz), -4.0);
+        im.update(0.0);
+        assert_eq!(im.get_axis_velocity(Axes::Horz), 0.0);
+    }
+
+    #[test]
+    fn test_bind_several_keys() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_keys_to_button(&[Keycode::Z, Keycode::Return, Keycode::Space], Buttons::A)
+            .bind_keys_to_axis(&[Keycode::Left, Keycode::A], Axes::Horz, false);
+        assert_eq!(im.bindings_iter().count(), 5);
+
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Space), Mod::empty(), false);
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        assert!(im.get_button(Buttons::A));
+        assert!(!im.get_button_released(Buttons::A));
+        im.update_keyup(Some(Keycode::Space), Mod::empty());
+        assert!(!im.get_button(Buttons::A));
+        assert!(im.get_button_released(Buttons::A));
+
+        im.update_keydown(Some(Keycode::A), Mod::empty(), false);
+        assert_eq!(im.get_axis_raw(Axes::Horz), -1.0);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::B);
+        im.update_keydown(Some(Keycode::X), Mod::empty(), false);
+        im.update(0.1);
+        let snap = im.snapshot();
+        assert_eq!(snap.clone(), snap);
+        assert!(im.diff(&snap).is_empty());
+
+        im.update_keyup(Some(Keycode::X), Mod::empty());
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(0.1);
+        let changes = im.diff(&snap);
+        assert_eq!(changes.len(), 3);
+        assert!(changes.contains(&InputChange::Button(Buttons::A, true)));
+        assert!(changes.contains(&InputChange::Button(Buttons::B, false)));
+
+        im.restore(&snap);
+        assert!(im.diff(&snap).is_empty());
+        assert_eq!(im.get_axis_raw(Axes::Horz), 0.0);
+        assert!(!im.get_button(Buttons::A));
+        assert!(im.get_button(Buttons::B));
+        assert!(!im.get_button_pressed(Buttons::B));
+    }
+
+    #[test]
+    fn test_update_frames() {
+        let make = || {
+            let mut im = InputManager::<Axes, Buttons>::new()
+                .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+                .bind_key_to_button(Keycode::Z, Buttons::A);
+            im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+            im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+            im
+        };
+        let mut by_dt = make();
+        let mut by_frames = make();
+        by_frames.set_fixed_timestep(0.02);
+        for _ in 0..5 {
+            by_dt.update(0.02);
+        }
+        by_frames.update_frames(5);
+        assert_eq!(by_frames.get_axis_raw(Axes::Horz), by_dt.get_axis_raw(Axes::Horz));
+        assert_eq!(by_frames.button_held_duration(Buttons::A),
+                   by_dt.button_held_duration(Buttons::A));
+        assert!(!by_frames.get_button_pressed(Buttons::A));
+    }
+
+    #[test]
+    fn test_long_press() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::B);
+        im.set_button_long_press(Buttons::A, 1.0);
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::X), Mod::empty(), false);
+        im.update(0.5);
+        assert_eq!(im.long_press_progress(Buttons::A), 0.5);
+        assert!(!im.get_long_press(Buttons::A));
+        assert_eq!(im.long_press_progress(Buttons::B), 0.0);
+        im.update(0.75);
+        assert_eq!(im.long_press_progress(Buttons::A), 1.0);
+        assert!(im.get_long_press(Buttons::A));
+
+        // Letting go early starts it over.
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        assert_eq!(im.long_press_progress(Buttons::A), 0.0);
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update(0.25);
+        assert_eq!(im.long_press_progress(Buttons::A), 0.25);
+    }
+
+    #[test]
+    fn test_axes_iter() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_axis_snap(Axes::Horz, true);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(0.1);
+        let mut axes: Vec<(Axes, f64, f64)> = im.axes_iter().collect();
+        axes.sort_by_key(|&(axis, _, _)| axis == Axes::Vert);
+        assert_eq!(axes, vec![(Axes::Horz, 1.0, 1.0), (Axes::Vert, 0.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_axis_asymmetric() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false);
+        im.set_axis_asymmetric(Axes::Horz, 1.0, 4.0, 1.0);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(0.25);
+        assert_eq!(im.get_axis(Axes::Horz), 0.25);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        im.update_keydown(Some(Keycode::Left), Mod::empty(), false);
+        im.update(0.25);
+        assert_eq!(im.get_axis(Axes::Horz), -0.75);
+    }
+
+    #[test]
+    fn test_mouse_drag() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_mouse_drag_to_axes(MouseButton::Left, Axes::Horz, Axes::Vert, 100.0);
+        im.update_mouse_position(200, 200);
+        assert_eq!(im.get_axes_vector(Axes::Horz, Axes::Vert), (0.0, 0.0));
+        im.update_mousedown(MouseButton::Left);
+        im.update_mouse_position(250, 150);
+        assert_eq!(im.get_axes_vector(Axes::Horz, Axes::Vert), (0.5, -0.5));
+        im.update(0.1);
+        assert_eq!(im.get_axes_vector(Axes::Horz, Axes::Vert), (0.5, -0.5));
+        im.update_mouse_position(500, 150);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+
+        im.update_mouseup(MouseButton::Left);
+        assert_eq!(im.get_axes_vector(Axes::Horz, Axes::Vert), (0.0, 0.0));
+        im.update_mouse_position(0, 0);
+        assert_eq!(im.get_axes_vector(Axes::Horz, Axes::Vert), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_add_bindings_in_place() {
+        let mut im = InputManager::<Axes, Buttons>::new();
+        for &(key, button) in &[(Keycode::Z, Buttons::A), (Keycode::X, Buttons::B)] {
+            im.add_key_button(key, button);
+        }
+        im.add_key_axis(Keycode::Right, Axes::Horz, true);
+        assert_eq!(im.binding_for_key(Keycode::X), Some(BoundEffect::Button(Buttons::B)));
+        assert_eq!(im.binding_for_key(Keycode::Right),
+                   Some(BoundEffect::Axis(Axes::Horz, 1.0)));
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert!(im.get_button_pressed(Buttons::A));
+    }
+
+    #[test]
+    fn test_linked_axes() {
+        let mut im = InputManager::<Axes, Buttons>::new();
+        im.add_key_axis(Keycode::Right, Axes::Horz, true);
+        im.add_key_axis(Keycode::Up, Axes::Vert, true);
+        im.set_axis_snap(Axes::Horz, true);
+        im.set_axis_snap(Axes::Vert, true);
+        im.link_axes_as_vector(Axes::Horz, Axes::Vert);
+
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(0.1);
+        assert_eq!(im.get_axes_vector(Axes::Horz, Axes::Vert), (1.0, 0.0));
+
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.1);
+        let (x, y) = im.get_axes_vector(Axes::Horz, Axes::Vert);
+        assert!((x - f64::sqrt(0.5)).abs() < 1e-10);
+        assert!((y - f64::sqrt(0.5)).abs() < 1e-10);
+        assert!((im.get_axis_velocity(Axes::Horz) - (f64::sqrt(0.5) - 1.0) / 0.1).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_has_binding() {
         let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false)
             .bind_key_to_button(Keycode::Z, Buttons::A)
-            .bind_key_to_button(Keycode::X, Buttons::B)
-            .bind_key_to_button(Keycode::Return, Buttons::Start)
-            .bind_key_to_button(Keycode::RShift, Buttons::Select)
+            .bind_chord_to_button(vec![Keycode::LCtrl, Keycode::S], Buttons::Select);
+        assert!(im.has_binding_for_axis(Axes::Horz));
+        assert!(!im.has_binding_for_axis(Axes::Vert));
+        assert!(im.has_binding_for_button(Buttons::A));
+        assert!(im.has_binding_for_button(Buttons::Select));
+        assert!(!im.has_binding_for_button(Buttons::B));
+
+        // Unbinding leaves the button's state around, but it's no
+        // longer bound.
+        im.unbind_key(Keycode::Z);
+        assert!(!im.has_binding_for_button(Buttons::A));
+    }
+
+    #[test]
+    fn test_axis_clamp() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_mouse_motion_to_axis(MouseAxis::X, Axes::Vert);
+        im.set_axis_clamp(Axes::Horz, false);
+        im.set_axis_clamp(Axes::Vert, false);
+        im.set_axis_hold(Axes::Vert, true);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), 4.0);
+        im.update_mouse_motion(150, 0);
+        im.update_mouse_motion(150, 0);
+        assert_eq!(im.get_axis(Axes::Vert), 3.0);
+
+        im.set_axis_clamp(Axes::Horz, true);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+    }
+
+    #[test]
+    fn test_mouse_and_key_button() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_mouse_to_button(MouseButton::Left, Buttons::A)
+            .bind_key_to_button(Keycode::Space, Buttons::A);
+        im.update_mousedown(MouseButton::Left);
+        im.update_keydown(Some(Keycode::Space), Mod::empty(), false);
+        im.update(0.1);
+        im.update_mouseup(MouseButton::Left);
+        assert!(im.get_button(Buttons::A));
+        assert!(!im.get_button_released(Buttons::A));
+        im.update_keyup(Some(Keycode::Space), Mod::empty());
+        assert!(!im.get_button(Buttons::A));
+        assert!(im.get_button_released(Buttons::A));
+    }
+
+    #[test]
+    fn test_oneshot() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::A);
+        im.set_button_oneshot(Buttons::A, 0.3);
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update(0.1);
+        im.update(0.1);
+        assert!(im.get_button(Buttons::A));
+        im.update(0.1);
+        assert!(!im.get_button(Buttons::A));
+        assert!(im.get_button_released(Buttons::A));
+        im.update(0.1);
+        assert!(!im.get_button_released(Buttons::A));
+
+        // Another key holding the button doesn't press it again...
+        im.update_keydown(Some(Keycode::X), Mod::empty(), false);
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        assert!(!im.get_button(Buttons::A));
+        // ...until they're both let go.
+        im.update_keyup(Some(Keycode::X), Mod::empty());
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert!(im.get_button_pressed(Buttons::A));
+    }
+
+    #[test]
+    fn test_axis_history() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true);
+        im.set_axis_tween(Axes::Horz, 1.0, 1.0);
+        im.enable_axis_history(Axes::Horz, 3);
+        assert!(im.axis_history(Axes::Horz).is_empty());
+        assert!(im.axis_history(Axes::Vert).is_empty());
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        for _ in 0..4 {
+            im.update(0.25);
+        }
+        assert_eq!(im.axis_history(Axes::Horz), &[0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn test_drain_events() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+        im.set_axis_snap(Axes::Horz, true);
+        tap(&mut im, Keycode::Z);
+        assert!(im.drain_events().is_empty());
+
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        tap(&mut im, Keycode::Z);
+        im.update(0.1);
+        im.update(0.1);
+        assert_eq!(im.drain_events(),
+                   vec![InputAction::ButtonPressed(Buttons::A),
+                        InputAction::ButtonReleased(Buttons::A),
+                        InputAction::AxisChanged {
+                            axis: Axes::Horz,
+                            value: 1.0,
+                        }]);
+        assert!(im.drain_events().is_empty());
+    }
+
+    #[test]
+    fn test_axis_spring() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true);
+        im.set_axis_snap(Axes::Horz, true);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(0.1);
+        im.set_axis_snap(Axes::Horz, false);
+        im.set_axis_spring(Axes::Horz, 100.0, 5.0);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        let mut lowest = 1.0;
+        for _ in 0..600 {
+            im.update(1.0 / 60.0);
+            lowest = f64::min(lowest, im.get_axis(Axes::Horz));
+        }
+        assert!(lowest < 0.0);
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+    }
+
+    #[test]
+    fn test_time_since_press() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+        assert_eq!(im.time_since_press(Buttons::A), None);
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert_eq!(im.time_since_press(Buttons::A), Some(0.0));
+        im.update(0.5);
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        assert_eq!(im.time_since_release(Buttons::A), Some(0.0));
+        im.update(0.25);
+        assert_eq!(im.time_since_press(Buttons::A), Some(0.75));
+        assert_eq!(im.time_since_release(Buttons::A), Some(0.25));
+        assert_eq!(im.time_since_press(Buttons::B), None);
+    }
+
+    #[test]
+    fn test_describe_bindings() {
+        let im = InputManager::<Axes, Buttons>::new()
             .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
-            .bind_key_to_axis(Keycode::Down, Axes::Vert, false)
             .bind_key_to_axis(Keycode::Left, Axes::Horz, false)
-            .bind_key_to_axis(Keycode::Right, Axes::Horz, true);
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+        assert_eq!(im.describe_bindings(),
+                   "Left -> Axis(Horz, -)\nUp -> Axis(Vert, +)\nZ -> Button(A)");
+    }
 
-        im.update_keydown(Some(Keycode::Z));
+    #[test]
+    fn test_toggle_button() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::C, Buttons::A)
+            .bind_key_to_button(Keycode::LCtrl, Buttons::A);
+        im.set_button_toggle(Buttons::A, true);
+        tap(&mut im, Keycode::C);
         assert!(im.get_button(Buttons::A));
-        assert!(im.get_button_down(Buttons::A));
-        im.update_keyup(Some(Keycode::Z));
+        assert!(im.get_button_pressed(Buttons::A));
+        im.update(0.1);
+
+        // Pressing another key while one's already held
+        // doesn't flip it again.
+        im.update_keydown(Some(Keycode::C), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::LCtrl), Mod::empty(), false);
         assert!(!im.get_button(Buttons::A));
-        assert!(im.get_button_up(Buttons::A));
+        assert!(im.get_button_released(Buttons::A));
+        im.update_keyup(Some(Keycode::C), Mod::empty());
+        im.update_keyup(Some(Keycode::LCtrl), Mod::empty());
+        assert!(!im.get_button(Buttons::A));
+        tap(&mut im, Keycode::LCtrl);
+        assert!(im.get_button(Buttons::A));
+    }
 
-        // Push the 'up' button, watch the axis
-        // increase to 1.0 but not beyond
-        im.update_keydown(Some(Keycode::Up));
-        assert!(im.get_axis_raw(Axes::Vert) > 0.0);
-        while im.get_axis(Axes::Vert) < 0.99 {
-            im.update(0.16);
+    #[test]
+    fn test_axis_max() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false);
+        im.set_axis_max(Axes::Horz, 0.5);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(0.1);
+        assert!((im.get_axis(Axes::Horz) - 0.4).abs() < 1e-10);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), 0.5);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        im.update_keydown(Some(Keycode::Left), Mod::empty(), false);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), -0.5);
+        im.update_keyup(Some(Keycode::Left), Mod::empty());
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+    }
+
+    #[test]
+    fn test_keymap() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::Z, Buttons::B)
+            .bind_mouse_to_button(MouseButton::Left, Buttons::A);
+        let mut map = HashMap::new();
+        map.insert(Keycode::Space, BoundEffect::Button(Buttons::A));
+        map.insert(Keycode::Up, BoundEffect::Axis(Axes::Vert, 1.0));
+        im.apply_keymap(map.clone());
+        assert_eq!(im.binding_for_key(Keycode::Z), None);
+        assert_eq!(im.export_keymap(), map);
+        im.update_mousedown(MouseButton::Left);
+        assert!(im.get_button(Buttons::A));
+
+        let im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::Z, Buttons::B);
+        assert_eq!(im.export_keymap()[&Keycode::Z], BoundEffect::Button(Buttons::A));
+    }
+
+    #[test]
+    fn test_axis_clamped_at_zero() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis_clamped(Keycode::W, Axes::Vert, true, true)
+            .bind_key_to_axis(Keycode::S, Axes::Vert, false);
+        im.update_keydown(Some(Keycode::W), Mod::empty(), false);
+        im.update(0.1);
+        im.update_keyup(Some(Keycode::W), Mod::empty());
+        im.update_keydown(Some(Keycode::S), Mod::empty(), false);
+        for _ in 0..10 {
+            im.update(0.1);
             assert!(im.get_axis(Axes::Vert) >= 0.0);
-            assert!(im.get_axis(Axes::Vert) <= 1.0);
         }
-        // Release it, watch it wind down
-        im.update_keyup(Some(Keycode::Up));
-        while im.get_axis(Axes::Vert) > 0.01 {
-            im.update(0.16);
-            assert!(im.get_axis(Axes::Vert) >= 0.0)
+        assert_eq!(im.get_axis(Axes::Vert), 0.0);
+        im.set_axis_snap(Axes::Vert, true);
+        tap(&mut im, Keycode::S);
+        assert_eq!(im.get_axis(Axes::Vert), 0.0);
+    }
+
+    #[test]
+    fn test_axis_magnitude() {
+        let shift = Mod::from_bits_truncate(1);
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_mod_to_axis_with_magnitude(Keycode::Right, shift, Axes::Horz, true, 0.5)
+            .bind_key_to_axis_with_magnitude(Keycode::Left, Axes::Horz, false, 0.25);
+        im.update_keydown(Some(Keycode::Right), shift, false);
+        im.update(0.1);
+        assert!((im.get_axis(Axes::Horz) - 0.4).abs() < 1e-10);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), 0.5);
+        im.update_keyup(Some(Keycode::Right), shift);
+
+        im.update_keydown(Some(Keycode::Left), Mod::empty(), false);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), -0.25);
+        im.update_keyup(Some(Keycode::Left), Mod::empty());
+
+        // Going from a full push to a partial one falls back to it.
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        im.update_keydown(Some(Keycode::Right), shift, false);
+        im.update(0.1);
+        assert!((im.get_axis(Axes::Horz) - 0.7).abs() < 1e-10);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Horz), 0.5);
+    }
+
+    #[test]
+    fn test_get_direction() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false)
+            .bind_key_to_axis_with_magnitude(Keycode::Up, Axes::Vert, true, 0.5);
+        assert_eq!(im.get_direction(Axes::Horz), 0);
+        im.update_keydown(Some(Keycode::Left), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        assert_eq!(im.get_direction(Axes::Horz), -1);
+        assert_eq!(im.get_direction(Axes::Vert), 1);
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+    }
+
+    #[test]
+    fn test_axis_initial() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
+            .bind_key_to_axis(Keycode::Down, Axes::Vert, false);
+        im.set_axis_initial(Axes::Vert, 0.5);
+        assert_eq!(im.get_axis(Axes::Vert), 0.5);
+        im.update_keydown(Some(Keycode::Down), Mod::empty(), false);
+        im.update(0.25);
+        assert_eq!(im.get_axis(Axes::Vert), -0.5);
+        im.update_keyup(Some(Keycode::Down), Mod::empty());
+        im.update(0.1);
+        assert!((im.get_axis(Axes::Vert) - -0.2).abs() < 1e-10);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Vert), 0.5);
+
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Vert), 1.0);
+        im.reset_axis(Axes::Vert);
+        assert_eq!(im.get_axis(Axes::Vert), 0.5);
+    }
+
+    #[test]
+    fn test_global_axis_scale() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_global_axis_scale_key(Keycode::LShift, 0.5);
+        im.set_axis_snap(Axes::Horz, true);
+        im.set_axis_snap(Axes::Vert, true);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::LShift), Mod::empty(), false);
+        assert_eq!(im.get_axes_vector(Axes::Horz, Axes::Vert), (0.5, 0.5));
+        im.update_keyup(Some(Keycode::LShift), Mod::empty());
+        assert_eq!(im.get_axes_vector(Axes::Horz, Axes::Vert), (1.0, 1.0));
+    }
+
+    #[test]
+    fn test_any_input() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false)
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+        tap(&mut im, Keycode::Escape);
+        assert!(!im.any_input_this_frame());
+        im.update_keydown(Some(Keycode::Left), Mod::empty(), false);
+        assert!(im.any_input_this_frame());
+        im.update(0.1);
+        assert!(!im.any_input_this_frame());
+        tap(&mut im, Keycode::Z);
+        assert!(im.any_input_this_frame());
+    }
+
+    #[test]
+    fn test_stepped_axis() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_stepped_axis(Keycode::Up, Axes::Vert, 0.25)
+            .bind_key_to_stepped_axis(Keycode::Down, Axes::Vert, -0.25);
+        assert!(im.update_keydown(Some(Keycode::Up), Mod::empty(), false));
+        assert_eq!(im.get_axis(Axes::Vert), 0.25);
+        im.update(1.0);
+        assert_eq!(im.get_axis(Axes::Vert), 0.25);
+        im.update_keyup(Some(Keycode::Up), Mod::empty());
+        for _ in 0..6 {
+            tap(&mut im, Keycode::Up);
         }
+        assert_eq!(im.get_axis(Axes::Vert), 1.0);
+        tap(&mut im, Keycode::Down);
+        assert_eq!(im.get_axis(Axes::Vert), 0.75);
+    }
 
-        // Do the same with the 'down' button.
-        im.update_keydown(Some(Keycode::Down));
-        while im.get_axis(Axes::Vert) > -0.99 {
-            im.update(0.16);
-            assert!(im.get_axis(Axes::Vert) <= 0.0);
-            assert!(im.get_axis(Axes::Vert) >= -1.0);
+    #[test]
+    fn test_with_capacity() {
+        let im = InputManager::<Axes, Buttons>::with_capacity(16, 2, 4)
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+        assert_eq!(im.binding_for_key(Keycode::Z), Some(BoundEffect::Button(Buttons::A)));
+        let im: InputManager<Axes, Buttons> = Default::default();
+        assert!(!im.any_button_pressed());
+    }
+
+    #[test]
+    fn test_axis_saturation() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true);
+        im.set_axis_range(Axes::Vert, 0.0, 1.0);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update(0.1);
+        assert_eq!(im.axis_saturation_count(Axes::Horz), 0);
+        for _ in 0..3 {
+            im.update(0.25);
         }
+        assert_eq!(im.axis_saturation_count(Axes::Horz), 3);
+        assert_eq!(im.axis_saturation_count(Axes::Vert), 0);
+        im.reset_saturation_counters();
+        assert_eq!(im.axis_saturation_count(Axes::Horz), 0);
+        im.update_axis_analog(Axes::Vert, 5.0);
+        im.update(0.1);
+        assert_eq!(im.axis_saturation_count(Axes::Vert), 1);
+    }
+
+    #[test]
+    fn test_axis_value() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis_value(Keycode::A, Axes::Horz, -0.5)
+            .bind_key_to_axis_value(Keycode::D, Axes::Horz, 3.0);
+        im.set_axis_snap(Axes::Horz, true);
+        im.update_keydown(Some(Keycode::A), Mod::empty(), false);
+        assert_eq!(im.get_axis(Axes::Horz), -0.5);
+        assert_eq!(im.get_axis_raw(Axes::Horz), -0.5);
+        im.update_keyup(Some(Keycode::A), Mod::empty());
+        im.update_keydown(Some(Keycode::D), Mod::empty(), false);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+        assert_eq!(im.binding_for_key(Keycode::A), Some(BoundEffect::Axis(Axes::Horz, -0.5)));
+        assert_eq!(im.binding_for_key(Keycode::D), Some(BoundEffect::Axis(Axes::Horz, 1.0)));
+        assert_eq!(im.describe_bindings(), "A -> Axis(Horz, -0.5)\nD -> Axis(Horz, +)");
+
+        // Saving and loading keeps the value.
+        let mut im2 = InputManager::<Axes, Buttons>::new();
+        im2.import_bindings(im.export_bindings());
+        assert_eq!(im2.binding_for_key(Keycode::A), Some(BoundEffect::Axis(Axes::Horz, -0.5)));
+        let mut im3 = InputManager::<Axes, Buttons>::new();
+        im3.apply_keymap(im.export_keymap());
+        assert_eq!(im3.binding_for_key(Keycode::A), Some(BoundEffect::Axis(Axes::Horz, -0.5)));
+        im3.set_axis_snap(Axes::Horz, true);
+        im3.update_keydown(Some(Keycode::A), Mod::empty(), false);
+        assert_eq!(im3.get_axis(Axes::Horz), -0.5);
+    }
+
+    #[test]
+    fn test_rebind_swap() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::B)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_swap_bindings_on_conflict(true);
+
+        im.begin_rebind(BoundEffect::Button(Buttons::A));
+        im.update_keydown(Some(Keycode::X), Mod::empty(), false);
+        assert_eq!(im.take_swapped_bindings(),
+                   vec![(BoundEffect::Button(Buttons::B), Keycode::Z)]);
+        assert_eq!(im.take_displaced_bindings(), vec![]);
+        assert_eq!(im.bindings_for_key(Keycode::X), vec![BoundEffect::Button(Buttons::A)]);
+        assert_eq!(im.bindings_for_key(Keycode::Z), vec![BoundEffect::Button(Buttons::B)]);
+
+        // A button can't take over an axis key, so it's just displaced.
+        im.update_keyup(Some(Keycode::X), Mod::empty());
+        im.begin_rebind(BoundEffect::Axis(Axes::Vert, 1.0));
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        assert_eq!(im.take_swapped_bindings(), vec![]);
+        assert_eq!(im.take_displaced_bindings(), vec![BoundEffect::Button(Buttons::B)]);
+        assert_eq!(im.bindings_for_key(Keycode::Up),
+                   vec![BoundEffect::Axis(Axes::Vert, 1.0)]);
+    }
+
+    #[test]
+    fn test_total_time() {
+        let mut im = InputManager::<Axes, Buttons>::new();
+        assert_eq!(im.total_time(), 0.0);
+        im.update(0.25);
+        im.update(0.5);
+        assert_eq!(im.total_time(), 0.75);
+        im.set_paused(true);
+        im.update(1.0);
+        assert_eq!(im.total_time(), 0.75);
+        im.set_paused(false);
+        im.update(-1.0);
+        assert_eq!(im.total_time(), 0.75);
+        im.reset_time();
+        assert_eq!(im.total_time(), 0.0);
+        im.update(0.5);
+        assert_eq!(im.total_time(), 0.5);
+    }
+
+    #[test]
+    fn test_axes_vector_snapped() {
+        let mut im = InputManager::<Axes, Buttons>::new();
+        assert_eq!(im.get_axes_vector_snapped(Axes::Horz, Axes::Vert, 8), (0.0, 0.0));
+        im.update_axis_analog(Axes::Horz, 0.9);
+        im.update_axis_analog(Axes::Vert, 0.3);
+
+        let (x, y) = im.get_axes_vector_snapped(Axes::Horz, Axes::Vert, 4);
+        assert!((x - f64::sqrt(0.9)).abs() < 1e-9);
+        assert!(y.abs() < 1e-9);
+        // That's closer to the diagonal than to straight right.
+        im.update_axis_analog(Axes::Vert, 0.6);
+        let (x, y) = im.get_axes_vector_snapped(Axes::Horz, Axes::Vert, 8);
+        assert!((x - y).abs() < 1e-9);
+        assert!((x * x + y * y - (0.81 + 0.36)).abs() < 1e-9);
+        // The stored state isn't touched.
+        assert_eq!(im.get_axes_vector(Axes::Horz, Axes::Vert), (0.9, 0.6));
+    }
+
+    #[test]
+    fn test_inject() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A);
+
+        im.inject_button(Buttons::A, true);
+        assert!(im.get_button_pressed(Buttons::A));
+        // A real press on top of it isn't a new press, and letting go
+        // of it doesn't release the injected one.
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update_keyup(Some(Keycode::Z), Mod::empty());
+        im.update(0.1);
+        assert!(im.get_button_down(Buttons::A));
+        im.inject_button(Buttons::A, false);
+        assert!(im.get_button_released(Buttons::A));
+
+        im.inject_axis(Axes::Horz, 1);
+        im.update(0.1);
+        assert!(im.get_axis(Axes::Horz) > 0.0);
+        assert_eq!(im.get_direction(Axes::Horz), 1);
+        im.inject_axis(Axes::Horz, -1);
+        assert_eq!(im.get_direction(Axes::Horz), -1);
+        im.inject_axis(Axes::Horz, 0);
+        assert_eq!(im.get_direction(Axes::Horz), 0);
+    }
+
+    #[test]
+    fn test_axis_at_rest() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true);
+        assert!(im.axis_at_rest(Axes::Horz));
+
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        assert!(!im.axis_at_rest(Axes::Horz));
+        im.update(1.0);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        // Let go of, but still on its way back.
+        assert!(!im.axis_at_rest(Axes::Horz));
+        im.update(1.0);
+        assert!(im.axis_at_rest(Axes::Horz));
+
+        im.update_axis_analog(Axes::Horz, 0.05);
+        assert!(!im.axis_at_rest(Axes::Horz));
+        im.set_axis_rest_tolerance(Axes::Horz, 0.1);
+        assert!(im.axis_at_rest(Axes::Horz));
+    }
+
+    #[test]
+    fn test_controller_hat() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_controller_hat_to_axes(0, Axes::Horz, Axes::Vert);
+
+        im.update_controller_hat(0, HatPosition::RightUp);
+        assert_eq!(im.get_direction(Axes::Horz), 1);
+        assert_eq!(im.get_direction(Axes::Vert), 1);
+        im.update_controller_hat(0, HatPosition::Up);
+        assert_eq!(im.get_direction(Axes::Horz), 0);
+        assert_eq!(im.get_direction(Axes::Vert), 1);
+        im.update_controller_hat(0, HatPosition::LeftDown);
+        assert_eq!(im.get_direction(Axes::Horz), -1);
+        assert_eq!(im.get_direction(Axes::Vert), -1);
+        // Some other hat does nothing.
+        im.update_controller_hat(1, HatPosition::Right);
+        assert_eq!(im.get_direction(Axes::Horz), -1);
+        im.update_controller_hat(0, HatPosition::Centered);
+        assert_eq!(im.get_direction(Axes::Horz), 0);
+        assert_eq!(im.get_direction(Axes::Vert), 0);
+    }
+
+    #[test]
+    fn test_encode_frame() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::B);
+        im.set_frame_layout(vec![Axes::Horz, Axes::Vert], vec![Buttons::A, Buttons::B]);
+        assert_eq!(im.encode_frame(), vec![0, 0, 0]);
+
+        im.update_keydown(Some(Keycode::X), Mod::empty(), false);
+        im.update_axis_analog(Axes::Horz, 1.0);
+        im.update_axis_analog(Axes::Vert, -0.5);
+        let frame = im.encode_frame();
+        assert_eq!(frame, vec![0b10, 127, (-64i8) as u8]);
+
+        let mut remote = InputManager::<Axes, Buttons>::new();
+        remote.set_frame_layout(vec![Axes::Horz, Axes::Vert], vec![Buttons::A, Buttons::B]);
+        assert_eq!(remote.decode_and_apply_frame(&frame), Ok(()));
+        assert!(!remote.get_button(Buttons::A));
+        assert!(remote.get_button_pressed(Buttons::B));
+        assert_eq!(remote.get_axis(Axes::Horz), 1.0);
+        assert!((remote.get_axis(Axes::Vert) + 0.5).abs() < 0.01);
+        remote.update(0.1);
+        assert!(remote.get_button_down(Buttons::B));
+        assert!((remote.get_axis(Axes::Vert) + 0.5).abs() < 0.01);
+        assert_eq!(remote.encode_frame(), frame);
+
+        // The wrong size is ignored.
+        assert_eq!(remote.decode_and_apply_frame(&[0]),
+                   Err(FrameSizeError {
+                       expected: 3,
+                       found: 1,
+                   }));
+        assert!(remote.get_button(Buttons::B));
+    }
+
+    #[test]
+    fn test_axis_tween_frames() {
+        assert_eq!(AxisTween::from_frames(6.0, 3.0, 60.0),
+                   Some(AxisTween {
+                       acceleration: 10.0,
+                       gravity: 20.0,
+                   }));
+        assert_eq!(AxisTween::from_frames(0.0, 0.0, 0.0), None);
+        assert_eq!(AxisTween::from_frames(-1.0, 1.0, 60.0), None);
+
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Right, Axes::Horz, true);
+        im.set_axis_tween_frames(Axes::Horz, 4.0, 2.0, 60.0);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update_frames(3);
+        assert!(im.get_axis(Axes::Horz) < 1.0);
+        im.update_frames(1);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        im.update_frames(2);
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+
+        assert!(im.set_axis_tween_frames(Axes::Horz, 0.0, 0.0, 60.0));
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update_frames(1);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        im.update_frames(1);
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+
+        // Instant still stops at the end of the range when not clamped.
+        im.set_axis_clamp(Axes::Horz, false);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update_frames(3);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+        assert!(!im.set_axis_tween_frames(Axes::Horz, 4.0, 2.0, 0.0));
+        im.update_frames(1);
+        assert_eq!(im.get_axis(Axes::Horz), 1.0);
+    }
+
+    #[test]
+    fn test_button_mask() {
+        let mut im = MockInputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(MockKey('z'), Buttons::A)
+            .bind_key_to_button(MockKey('x'), Buttons::B)
+            .bind_key_to_button(MockKey('\n'), Buttons::Start);
+        let buttons = [Buttons::A, Buttons::B, Buttons::Start];
+        assert_eq!(im.button_mask(&buttons), 0);
+
+        im.mock_press('z');
+        im.mock_press('\n');
+        assert_eq!(im.button_mask(&buttons), 0b101);
+        assert_eq!(im.button_mask(&[Buttons::Start, Buttons::B]), 0b01);
+
+        // Past 64, the rest are left out.
+        let mut many = vec![Buttons::B; 64];
+        many[63] = Buttons::A;
+        many.push(Buttons::Start);
+        assert_eq!(im.button_mask(&many), 1 << 63);
+    }
+
+    #[test]
+    fn test_last_nonzero_direction() {
+        let mut im = MockInputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(MockKey('a'), Axes::Horz, false)
+            .bind_key_to_axis(MockKey('d'), Axes::Horz, true);
+        assert_eq!(im.last_nonzero_direction(Axes::Horz), 0.0);
+
+        im.mock_tap('a');
+        im.update(1.0);
+        assert_eq!(im.get_direction(Axes::Horz), 0);
+        assert_eq!(im.last_nonzero_direction(Axes::Horz), -1.0);
+        im.mock_tap('d');
+        assert_eq!(im.last_nonzero_direction(Axes::Horz), 1.0);
+    }
+
+    #[test]
+    fn test_stick() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_stick(0, StickId::Left, Axes::Horz, Axes::Vert);
+        im.set_stick_deadzone(Axes::Horz, Axes::Vert, 0.2);
+
+        // Each axis is past 0.2 on its own, but not together.
+        im.update_controller_axis(0, Axis::LeftX, i16::MAX / 8);
+        im.update_controller_axis(0, Axis::LeftY, i16::MAX / 8);
+        assert_eq!(im.get_stick(Axes::Horz, Axes::Vert), (0.0, 0.0));
+
+        im.update_controller_axis(0, Axis::LeftX, i16::MAX);
+        im.update_controller_axis(0, Axis::LeftY, 0);
+        assert_eq!(im.get_stick(Axes::Horz, Axes::Vert), (1.0, 0.0));
+        im.update_controller_axis(0, Axis::LeftX, i16::MAX / 5 * 3);
+        let (x, y) = im.get_stick(Axes::Horz, Axes::Vert);
+        assert!((x - 0.5).abs() < 0.001);
+        assert_eq!(y, 0.0);
+        // The right stick isn't bound.
+        im.update_controller_axis(0, Axis::RightX, i16::MAX);
+        assert!((im.get_axis(Axes::Horz) - 0.6).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_instant_mode() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Left, Axes::Horz, false)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_instant_mode(true);
+
+        im.update_keydown(Some(Keycode::Left), Mod::empty(), false);
+        assert_eq!(im.get_axis(Axes::Horz), -1.0);
+        im.update(0.01);
+        assert_eq!(im.get_axis(Axes::Horz), im.get_axis_raw(Axes::Horz));
+        im.update_keyup(Some(Keycode::Left), Mod::empty());
+        assert_eq!(im.get_axis(Axes::Horz), 0.0);
+
+        im.set_instant_mode(false);
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.01);
+        assert!(im.get_axis(Axes::Vert) < 1.0);
+        // Turning it on catches up on the next update.
+        im.set_instant_mode(true);
+        im.update(0.01);
+        assert_eq!(im.get_axis(Axes::Vert), 1.0);
+    }
+
+    #[test]
+    fn test_instant_mode_hold() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_stepped_axis(Keycode::Right, Axes::Horz, 0.25)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_axis_hold(Axes::Vert, true);
+        im.update_keydown(Some(Keycode::Right), Mod::empty(), false);
+        im.update_keyup(Some(Keycode::Right), Mod::empty());
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im.update(0.1);
+        im.update_keyup(Some(Keycode::Up), Mod::empty());
+        let vert = im.get_axis(Axes::Vert);
+        assert!(vert > 0.0 && vert < 1.0);
+
+        im.set_instant_mode(true);
+        im.update(0.1);
+        assert_eq!(im.get_axis(Axes::Horz), 0.25);
+        assert_eq!(im.get_axis(Axes::Vert), vert);
+        // Pushing a held axis still jumps straight there.
+        im.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        assert_eq!(im.get_axis(Axes::Vert), 1.0);
+        im.update_keyup(Some(Keycode::Up), Mod::empty());
+        im.update(0.1);
+        assert_eq!(im.get_axis(Axes::Vert), 1.0);
+    }
+
+    #[test]
+    fn test_set_key_effect() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::B);
+
+        assert_eq!(im.set_key_effect(Keycode::Z, BoundEffect::Axis(Axes::Horz, 1.0)),
+                   Some(BoundEffect::Button(Buttons::A)));
+        assert_eq!(im.set_key_effect(Keycode::C, BoundEffect::Button(Buttons::Start)),
+                   None);
+        im.update_keydown(Some(Keycode::Z), Mod::empty(), false);
+        im.update_keydown(Some(Keycode::C), Mod::empty(), false);
+        assert!(!im.get_button(Buttons::A));
+        assert_eq!(im.get_direction(Axes::Horz), 1);
+        assert!(im.get_button(Buttons::Start));
+        // Other keys are left alone.
+        assert_eq!(im.binding_for_key(Keycode::X), Some(BoundEffect::Button(Buttons::B)));
+    }
+
+    #[test]
+    fn test_unused_actions() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true)
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_button(Keycode::X, Buttons::B);
+        // Known about, but not bound to anything.
+        im.set_button_toggle(Buttons::Start, true);
+        assert_eq!(im.unused_actions().len(), 3);
+
+        im.get_axis(Axes::Vert);
+        im.get_button_pressed(Buttons::A);
+        assert_eq!(im.unused_actions(), vec![Action::Button(Buttons::B)]);
+        im.get_button_down(Buttons::B);
+        assert_eq!(im.unused_actions(), vec![]);
+
+        im.reset_usage_tracking();
+        im.get_direction(Axes::Vert);
+        let unused = im.unused_actions();
+        assert_eq!(unused.len(), 2);
+        assert!(unused.contains(&Action::Button(Buttons::A)));
+        assert!(unused.contains(&Action::Button(Buttons::B)));
+
+        // Bindings outside the active context still count.
+        im.push_context("menu", true);
+        im.add_key_button(Keycode::Return, Buttons::Start);
+        let unused = im.unused_actions();
+        assert_eq!(unused.len(), 3);
+        assert!(unused.contains(&Action::Button(Buttons::Start)));
+    }
+
+    #[test]
+    fn test_clamp_vector_magnitude() {
+        assert_eq!(clamp_vector_magnitude(0.0, 0.0, 1.0), (0.0, 0.0));
+        assert_eq!(clamp_vector_magnitude(0.3, -0.4, 1.0), (0.3, -0.4));
+        assert_eq!(clamp_vector_magnitude(3.0, 4.0, 1.0), (0.6, 0.8));
+        assert_eq!(clamp_vector_magnitude(-3.0, 4.0, 2.5), (-1.5, 2.0));
+    }
+
+    #[test]
+    fn test_reserve_combo() {
+        let alt = Mod::from_bits_truncate(0x100);
+        let shift = Mod::from_bits_truncate(1);
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Return, Buttons::Start);
+        im.reserve_combo(Keycode::Return, alt);
+
+        assert!(!im.update_keydown(Some(Keycode::Return), alt | shift, false));
+        assert!(!im.get_button(Buttons::Start));
+        im.update_keyup(Some(Keycode::Return), alt | shift);
+
+        assert!(im.update_keydown(Some(Keycode::Return), shift, false));
+        assert!(im.get_button(Buttons::Start));
+        im.update_keyup(Some(Keycode::Return), shift);
+
+        im.begin_rebind(BoundEffect::Button(Buttons::A));
+        im.update_keydown(Some(Keycode::Return), alt, false);
+        assert!(im.rebind_pending());
+    }
+
+    #[test]
+    fn test_input_profile() {
+        let mut im = InputManager::<Axes, Buttons>::new()
+            .bind_key_to_button(Keycode::Z, Buttons::A)
+            .bind_key_to_axis(Keycode::Up, Axes::Vert, true);
+        im.set_axis_inverted(Axes::Vert, true);
+        im.set_axis_sensitivity(Axes::Horz, 2.0);
+        let profile = im.export_profile("player 1");
+        assert_eq!(profile.name, "player 1");
+
+        let mut im2 = InputManager::from_profile(profile.clone());
+        // The bindings come out in no particular order.
+        assert_eq!(im2.export_profile("player 1").axes, profile.axes);
+        assert_eq!(im2.binding_for_key(Keycode::Z), Some(BoundEffect::Button(Buttons::A)));
+        im2.update_keydown(Some(Keycode::Up), Mod::empty(), false);
+        im2.update(1.0);
+        assert_eq!(im2.get_axis(Axes::Vert), -1.0);
+
+        // A partial profile only changes what it has.
+        let mut axes = HashMap::new();
+        axes.insert(Axes::Vert,
+                    AxisSettings {
+                        deadzone: Some(0.5),
+                        ..AxisSettings::default()
+                    });
+        let unknown = im2.apply_profile(InputProfile {
+            name: "low vision".to_string(),
+            bindings: None,
+            axes: axes,
+        });
+        assert!(unknown.is_empty());
+        assert_eq!(im2.binding_for_key(Keycode::Z), Some(BoundEffect::Button(Buttons::A)));
+        assert_eq!(im2.get_axis(Axes::Vert), -1.0);
+        im2.update_axis_analog(Axes::Vert, 0.4);
+        assert_eq!(im2.get_axis(Axes::Vert), 0.0);
+    }
+
+    #[test]
+    fn test_mouse_position() {
+        let mut im = InputManager::<Axes, Buttons>::new();
+        assert_eq!(im.mouse_position(), (0, 0));
+        im.update_mouse_position(10, 20);
+        im.update_mouse_position(15, 30);
+        assert_eq!(im.mouse_position(), (15, 30));
+        assert_eq!(im.mouse_position_delta(), (15, 30));
+        im.update(0.16);
+        assert_eq!(im.mouse_position_delta(), (0, 0));
+        im.update_mouse_position(5, 35);
+        assert_eq!(im.mouse_position_delta(), (-10, 5));
     }
 }
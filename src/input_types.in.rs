@@ -0,0 +1,97 @@
+
+/// What a physical input is bound to: either a logical axis
+/// and how far along it, or a logical button.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum BoundEffect<Axes, Buttons>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone
+{
+    /// The axis, and the value from -1 to 1 the input pushes it
+    /// toward: -1 or 1 for all the way.
+    Axis(Axes, f64),
+    Button(Buttons),
+}
+
+
+/// A single key binding.  The key is stored by name,
+/// since `Keycode` doesn't serialize sensibly, and the
+/// modifiers as SDL's bitmask.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct KeyBinding<Axes, Buttons>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone
+{
+    key: String,
+    #[serde(default)]
+    mods: u16,
+    effect: BoundEffect<Axes, Buttons>,
+}
+
+
+/// A set of key bindings exported from an `InputManager`,
+/// for saving and loading with serde when the `serde` feature
+/// is on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Bindings<Axes, Buttons>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone
+{
+    keys: Vec<KeyBinding<Axes, Buttons>>,
+}
+
+
+/// The logical state of all the axes and buttons at one moment,
+/// from `InputManager::snapshot()`.  Each axis's position and
+/// direction are kept, and whether each button is down.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputSnapshot<Axes, Buttons>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone
+{
+    axes: HashMap<Axes, (f64, f64)>,
+    buttons: HashMap<Buttons, bool>,
+}
+
+
+/// One difference between two input states, from
+/// `InputManager::diff()`.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub enum InputChange<Axes, Buttons>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone
+{
+    /// The axis, and its new position and direction.
+    Axis(Axes, f64, f64),
+    /// The button, and whether it's now down.
+    Button(Buttons, bool),
+}
+
+
+/// The settings for one axis in an `InputProfile`.  Any left as
+/// `None` are left alone when the profile is applied.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq)]
+pub struct AxisSettings {
+    #[serde(default)]
+    pub sensitivity: Option<f64>,
+    #[serde(default)]
+    pub inverted: Option<bool>,
+    #[serde(default)]
+    pub deadzone: Option<f64>,
+}
+
+
+/// A player's named set of key bindings and axis settings, for
+/// saving and loading together with serde.  See
+/// `InputManager::apply_profile()` for how it's applied.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputProfile<Axes, Buttons>
+    where Axes: Eq + Hash + Clone,
+          Buttons: Eq + Hash + Clone
+{
+    pub name: String,
+    /// If present, replaces all the key bindings
+    #[serde(default)]
+    pub bindings: Option<Bindings<Axes, Buttons>>,
+    #[serde(default)]
+    pub axes: HashMap<Axes, AxisSettings>,
+}
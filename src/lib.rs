@@ -1,7 +1,9 @@
 extern crate ggez;
 extern crate rand;
 extern crate nalgebra as na;
+#[cfg(feature = "serde")]
 extern crate serde;
+#[cfg(test)]
 extern crate serde_json;
 
 pub mod asset;
@@ -10,6 +12,7 @@ pub mod input;
 pub mod particle;
 pub mod scene;
 pub mod sprite;
+#[cfg(feature = "serde")]
 pub mod sprite_loader;
 
 type Point2 = na::Point2<f64>;